@@ -0,0 +1,261 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::mem::size_of;
+use std::mem::MaybeUninit;
+use std::os::unix::io::AsFd;
+use std::os::unix::io::AsRawFd as _;
+use std::os::unix::io::BorrowedFd;
+use std::os::unix::io::FromRawFd as _;
+use std::os::unix::io::OwnedFd;
+use std::path::Path;
+
+use bitflags::bitflags;
+
+use crate::mprog;
+use crate::mprog::ProgQuery;
+use crate::util;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Link;
+use crate::ProgramAttachType;
+use crate::ProgramMut;
+use crate::Result;
+
+/// The conventional mount point of the cgroup v2 hierarchy.
+pub const CGROUP2_ROOT: &str = "/sys/fs/cgroup";
+
+// from kernel @ include/linux/exportfs.h
+const FILEID_KERNFS: i32 = 0xfe;
+
+/// The file handle identifying a cgroup, as understood by
+/// `open_by_handle_at(2)`.
+#[repr(C)]
+struct CgroupFileHandle {
+    handle_bytes: u32,
+    handle_type: i32,
+    cgroup_id: u64,
+}
+
+bitflags! {
+    /// Flags to configure the attachment of programs to a [`Cgroup`].
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct CgroupAttachFlags: u32 {
+        /// No flags. Only a single program may be attached and it may
+        /// not be overridden by programs in descendant cgroups.
+        const NONE           = 0;
+        /// See [`libbpf_sys::BPF_F_ALLOW_OVERRIDE`].
+        const ALLOW_OVERRIDE = libbpf_sys::BPF_F_ALLOW_OVERRIDE as _;
+        /// See [`libbpf_sys::BPF_F_ALLOW_MULTI`].
+        const ALLOW_MULTI    = libbpf_sys::BPF_F_ALLOW_MULTI as _;
+        /// See [`libbpf_sys::BPF_F_REPLACE`].
+        const REPLACE        = libbpf_sys::BPF_F_REPLACE as _;
+    }
+}
+
+/// The programs attached to a [`Cgroup`], as reported by
+/// [`Cgroup::query`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CgroupProgs {
+    /// The flags the programs were attached with. Not reported for
+    /// queries of effective programs.
+    pub attach_flags: CgroupAttachFlags,
+    /// The IDs of the attached programs, in execution order.
+    pub prog_ids: Vec<u32>,
+}
+
+/// A [cgroup](https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html)
+/// of the cgroup v2 hierarchy that BPF programs can be attached to.
+///
+/// Programs can either be attached using a [`Link`], as created by
+/// [`Cgroup::attach_link`], or using the legacy attachment API
+/// ([`Cgroup::attach`], [`Cgroup::replace`], [`Cgroup::detach`]). Note
+/// that legacy attachments outlive the process that created them.
+#[derive(Debug)]
+pub struct Cgroup {
+    fd: OwnedFd,
+}
+
+impl Cgroup {
+    /// Open the cgroup at the given path, e.g.,
+    /// `/sys/fs/cgroup/system.slice`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("failed to open cgroup `{}`", path.display()))?;
+        Ok(Self {
+            fd: OwnedFd::from(file),
+        })
+    }
+
+    /// Open the cgroup that the calling process is a member of.
+    pub fn current() -> Result<Self> {
+        let cgroups =
+            fs::read_to_string("/proc/self/cgroup").context("failed to read /proc/self/cgroup")?;
+        // The entry of the cgroup v2 hierarchy has the form `0::<path>`.
+        let path = cgroups
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .ok_or_else(|| {
+                Error::with_io_error(
+                    io::ErrorKind::NotFound,
+                    "process is not a member of a cgroup v2 hierarchy",
+                )
+            })?;
+        Self::open(Path::new(CGROUP2_ROOT).join(path.trim_start_matches('/')))
+    }
+
+    /// Open the cgroup with the given ID.
+    ///
+    /// The cgroup v2 hierarchy is expected to be mounted at
+    /// [`CGROUP2_ROOT`]. Opening a cgroup by ID requires the
+    /// `CAP_DAC_READ_SEARCH` capability.
+    pub fn from_id(id: u64) -> Result<Self> {
+        let root = File::open(CGROUP2_ROOT)
+            .with_context(|| format!("failed to open cgroup root `{CGROUP2_ROOT}`"))?;
+        let mut handle = CgroupFileHandle {
+            handle_bytes: size_of::<u64>() as _,
+            handle_type: FILEID_KERNFS,
+            cgroup_id: id,
+        };
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_open_by_handle_at,
+                root.as_raw_fd(),
+                &mut handle as *mut CgroupFileHandle,
+                libc::O_RDONLY | libc::O_CLOEXEC,
+            )
+        };
+        if ret < 0 {
+            return Err(Error::from(io::Error::last_os_error()))
+                .with_context(|| format!("failed to open cgroup with ID {id}"));
+        }
+        // SAFETY: `open_by_handle_at` returned a valid file descriptor,
+        //         which we now own.
+        let fd = unsafe { OwnedFd::from_raw_fd(ret as _) };
+        Ok(Self { fd })
+    }
+
+    /// Retrieve the ID of the cgroup.
+    pub fn id(&self) -> Result<u64> {
+        let mut stat = MaybeUninit::<libc::stat>::uninit();
+        let ret = unsafe { libc::fstat(self.fd.as_raw_fd(), stat.as_mut_ptr()) };
+        if ret < 0 {
+            return Err(Error::from(io::Error::last_os_error()))
+        }
+        // SAFETY: `fstat` initialized the structure.
+        let stat = unsafe { stat.assume_init() };
+        // The ID of a cgroup is the inode number of its directory.
+        Ok(stat.st_ino as u64)
+    }
+
+    /// Attach the program to the cgroup using a [`Link`], which
+    /// detaches the program once dropped.
+    ///
+    /// Multiple programs can be attached to the same cgroup and attach
+    /// type using links.
+    pub fn attach_link(&self, prog: &ProgramMut<'_>) -> Result<Link> {
+        prog.attach_cgroup(self.fd.as_raw_fd())
+    }
+
+    fn attach_impl(
+        &self,
+        prog: BorrowedFd<'_>,
+        attach_type: ProgramAttachType,
+        flags: CgroupAttachFlags,
+        replace: Option<BorrowedFd<'_>>,
+    ) -> Result<()> {
+        let mut opts = libbpf_sys::bpf_prog_attach_opts {
+            sz: size_of::<libbpf_sys::bpf_prog_attach_opts>() as _,
+            flags: flags.bits(),
+            ..Default::default()
+        };
+        if let Some(replace) = replace {
+            opts.__bindgen_anon_1.replace_prog_fd = replace.as_raw_fd();
+        }
+
+        let ret = unsafe {
+            libbpf_sys::bpf_prog_attach_opts(
+                prog.as_raw_fd(),
+                self.fd.as_raw_fd(),
+                attach_type as u32,
+                &opts,
+            )
+        };
+        util::parse_ret(ret)
+    }
+
+    /// Attach the program to the cgroup using the legacy attachment
+    /// API.
+    ///
+    /// # Notes
+    /// Once a program is attached, it will outlive the userspace program. Make
+    /// sure to detach the program if its not desired.
+    pub fn attach(
+        &self,
+        prog: BorrowedFd<'_>,
+        attach_type: ProgramAttachType,
+        flags: CgroupAttachFlags,
+    ) -> Result<()> {
+        self.attach_impl(prog, attach_type, flags, None)
+            .context("failed to attach program to cgroup")
+    }
+
+    /// Atomically replace `old_prog`, which has been attached with
+    /// [`CgroupAttachFlags::ALLOW_MULTI`], with `prog`.
+    ///
+    /// [`CgroupAttachFlags::REPLACE`] is implied.
+    pub fn replace(
+        &self,
+        prog: BorrowedFd<'_>,
+        old_prog: BorrowedFd<'_>,
+        attach_type: ProgramAttachType,
+        flags: CgroupAttachFlags,
+    ) -> Result<()> {
+        let flags = flags | CgroupAttachFlags::REPLACE;
+        self.attach_impl(prog, attach_type, flags, Some(old_prog))
+            .context("failed to replace program attached to cgroup")
+    }
+
+    /// Detach a program attached using the legacy attachment API from
+    /// the cgroup.
+    pub fn detach(&self, prog: BorrowedFd<'_>, attach_type: ProgramAttachType) -> Result<()> {
+        let ret = unsafe {
+            libbpf_sys::bpf_prog_detach2(prog.as_raw_fd(), self.fd.as_raw_fd(), attach_type as u32)
+        };
+        util::parse_ret(ret).context("failed to detach program from cgroup")
+    }
+
+    /// Query the programs attached to the cgroup for the given attach
+    /// type.
+    ///
+    /// If `effective` is set, the programs that are effectively run for
+    /// the cgroup are reported, including the ones inherited from
+    /// ancestor cgroups.
+    pub fn query(&self, attach_type: ProgramAttachType, effective: bool) -> Result<CgroupProgs> {
+        let attach_type = attach_type as u32;
+        let query_flags = if effective {
+            libbpf_sys::BPF_F_QUERY_EFFECTIVE
+        } else {
+            0
+        };
+
+        let ProgQuery {
+            attach_flags,
+            prog_ids,
+            ..
+        } = mprog::query_progs(self.fd.as_raw_fd(), attach_type, query_flags, false)
+            .context("failed to query programs attached to cgroup")?;
+
+        Ok(CgroupProgs {
+            attach_flags: CgroupAttachFlags::from_bits_retain(attach_flags),
+            prog_ids,
+        })
+    }
+}
+
+impl AsFd for Cgroup {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
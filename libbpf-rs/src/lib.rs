@@ -79,29 +79,49 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
 pub mod btf;
+mod cgroup;
 mod error;
 mod iter;
 mod link;
 mod linker;
+mod lpm_trie;
 mod map;
+mod map_of_maps;
+mod mmap;
+mod mprog;
 mod netfilter;
+mod netkit;
 mod object;
+mod percpu;
 mod perf_buffer;
+mod perf_event;
 mod print;
 mod program;
 pub mod query;
+mod queue;
 mod ringbuf;
+mod ringbuf_reader;
 mod skeleton;
+mod sockmap;
 mod tc;
+mod tcx;
+mod typed_map;
 mod user_ringbuf;
 mod util;
 mod xdp;
+mod xdp_map;
+mod xsk;
 
 pub use libbpf_sys;
+pub use plain;
 
 pub use crate::btf::Btf;
 pub use crate::btf::HasSize;
 pub use crate::btf::ReferencesType;
+pub use crate::cgroup::Cgroup;
+pub use crate::cgroup::CgroupAttachFlags;
+pub use crate::cgroup::CgroupProgs;
+pub use crate::cgroup::CGROUP2_ROOT;
 pub use crate::error::Error;
 pub use crate::error::ErrorExt;
 pub use crate::error::ErrorKind;
@@ -109,18 +129,27 @@ pub use crate::error::Result;
 pub use crate::iter::Iter;
 pub use crate::link::Link;
 pub use crate::linker::Linker;
+pub use crate::lpm_trie::LpmKey;
 pub use crate::map::Map;
 pub use crate::map::MapCore;
 pub use crate::map::MapFlags;
 pub use crate::map::MapHandle;
 pub use crate::map::MapImpl;
 pub use crate::map::MapInfo;
+pub use crate::map::MapKeyCursor;
 pub use crate::map::MapKeyIter;
 pub use crate::map::MapMut;
 pub use crate::map::MapType;
 pub use crate::map::OpenMap;
 pub use crate::map::OpenMapImpl;
 pub use crate::map::OpenMapMut;
+pub use crate::map_of_maps::InnerMapSpec;
+pub use crate::map_of_maps::MapOfMaps;
+pub use crate::mmap::MmapArray;
+pub use crate::mprog::MprogAnchor;
+pub use crate::mprog::MprogEntry;
+pub use crate::mprog::MprogPosition;
+pub use crate::mprog::MprogProgs;
 pub use crate::netfilter::NetfilterOpts;
 pub use crate::netfilter::NFPROTO_IPV4;
 pub use crate::netfilter::NFPROTO_IPV6;
@@ -129,33 +158,62 @@ pub use crate::netfilter::NF_INET_LOCAL_IN;
 pub use crate::netfilter::NF_INET_LOCAL_OUT;
 pub use crate::netfilter::NF_INET_POST_ROUTING;
 pub use crate::netfilter::NF_INET_PRE_ROUTING;
+pub use crate::netkit::NetkitOpts;
 pub use crate::object::AsRawLibbpf;
 pub use crate::object::MapIter;
 pub use crate::object::Object;
 pub use crate::object::ObjectBuilder;
 pub use crate::object::OpenObject;
 pub use crate::object::ProgIter;
+pub use crate::percpu::PerCpuBatchIter;
+pub use crate::percpu::PerCpuMap;
+pub use crate::percpu::PerCpuValues;
+#[cfg(feature = "tokio")]
+pub use crate::perf_buffer::AsyncPerfBuffer;
 pub use crate::perf_buffer::PerfBuffer;
 pub use crate::perf_buffer::PerfBufferBuilder;
+pub use crate::perf_buffer::PerfBufferStats;
+pub use crate::perf_buffer::PerfRecord;
+pub use crate::perf_buffer::PerfRecordKind;
+pub use crate::perf_event::HardwareEvent;
+pub use crate::perf_event::PerfEvent;
+pub use crate::perf_event::SoftwareEvent;
 pub use crate::print::get_print;
 pub use crate::print::set_print;
 pub use crate::print::PrintCallback;
 pub use crate::print::PrintLevel;
 pub use crate::program::Input as ProgramInput;
+pub use crate::program::KprobeMultiOpts;
+pub use crate::program::KprobeOpts;
 pub use crate::program::OpenProgram;
 pub use crate::program::OpenProgramImpl;
 pub use crate::program::OpenProgramMut;
 pub use crate::program::Output as ProgramOutput;
+pub use crate::program::PerfEventOpts;
+pub use crate::program::ProbeAttachMode;
 pub use crate::program::Program;
 pub use crate::program::ProgramAttachType;
 pub use crate::program::ProgramImpl;
 pub use crate::program::ProgramMut;
 pub use crate::program::ProgramType;
+pub use crate::program::TraceOpts;
 pub use crate::program::TracepointOpts;
+pub use crate::program::UprobeMultiOpts;
 pub use crate::program::UprobeOpts;
 pub use crate::program::UsdtOpts;
+pub use crate::queue::BloomFilterMap;
+pub use crate::queue::QueueDrain;
+pub use crate::queue::QueueMap;
+#[cfg(feature = "tokio")]
+pub use crate::ringbuf::AsyncRingBuffer;
+pub use crate::ringbuf::Ring;
 pub use crate::ringbuf::RingBuffer;
 pub use crate::ringbuf::RingBufferBuilder;
+pub use crate::ringbuf_reader::RingBufferReader;
+pub use crate::ringbuf_reader::RingBufferSample;
+pub use crate::sockmap::socket_cookie;
+pub use crate::sockmap::SockHash;
+pub use crate::sockmap::SockMap;
 pub use crate::tc::TcAttachPoint;
 pub use crate::tc::TcHook;
 pub use crate::tc::TcHookBuilder;
@@ -166,11 +224,34 @@ pub use crate::tc::TC_H_INGRESS;
 pub use crate::tc::TC_H_MIN_EGRESS;
 pub use crate::tc::TC_H_MIN_INGRESS;
 pub use crate::tc::TC_INGRESS;
+pub use crate::tcx::Tcx;
+pub use crate::tcx::TcxDirection;
+pub use crate::tcx::TcxOpts;
+pub use crate::typed_map::TypedBatchIter;
+pub use crate::typed_map::TypedKeyIter;
+pub use crate::typed_map::TypedMap;
+pub use crate::typed_map::TypedMapIter;
+#[cfg(feature = "tokio")]
+pub use crate::user_ringbuf::AsyncUserRingBuffer;
 pub use crate::user_ringbuf::UserRingBuffer;
 pub use crate::user_ringbuf::UserRingBufferSample;
+pub use crate::user_ringbuf::UserRingBufferTypedSample;
 pub use crate::util::num_possible_cpus;
+pub use crate::util::NoPadding;
 pub use crate::xdp::Xdp;
+pub use crate::xdp::XdpAttachMode;
+pub use crate::xdp::XdpFeatures;
 pub use crate::xdp::XdpFlags;
+pub use crate::xdp::XdpInfo;
+pub use crate::xdp_map::CpuMap;
+pub use crate::xdp_map::CpuMapValue;
+pub use crate::xdp_map::DevMap;
+pub use crate::xdp_map::DevMapValue;
+pub use crate::xdp_map::XskMap;
+pub use crate::xsk::XdpDesc;
+pub use crate::xsk::XskBindFlags;
+pub use crate::xsk::XskConfig;
+pub use crate::xsk::XskSocket;
 
 /// An unconstructible dummy type used for tagging mutable type
 /// variants.
@@ -5,6 +5,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::ptr::NonNull;
 
+use crate::query::LinkInfo;
 use crate::util;
 use crate::util::validate_bpf_ret;
 use crate::AsRawLibbpf;
@@ -108,6 +109,11 @@ impl Link {
         let ret = unsafe { libbpf_sys::bpf_link__detach(self.ptr.as_ptr()) };
         util::parse_ret(ret)
     }
+
+    /// Retrieve information about the link from the kernel.
+    pub fn info(&self) -> Result<LinkInfo> {
+        LinkInfo::load_from_fd(self.as_fd())
+    }
 }
 
 impl AsRawLibbpf for Link {
@@ -0,0 +1,241 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::str::FromStr;
+
+use plain::Plain;
+
+use crate::Error;
+use crate::MapFlags;
+use crate::NoPadding;
+use crate::Result;
+use crate::TypedMap;
+
+/// A key of a [`MapType::LpmTrie`][crate::MapType::LpmTrie] map.
+///
+/// This type has the in-memory representation of the kernel's
+/// `struct bpf_lpm_trie_key`: a native-endian prefix length, followed
+/// by `N` bytes of data in network byte order. Data bits beyond the
+/// prefix are always zero.
+///
+/// `LpmKey<4>` and `LpmKey<16>` can be created from and converted to
+/// IPv4 and IPv6 networks, respectively, e.g., by parsing CIDR notation
+/// such as `10.0.0.0/8`.
+///
+/// `LpmKey` is meant to be used together with [`TypedMap`]:
+/// ```no_run
+/// # use libbpf_rs::LpmKey;
+/// # use libbpf_rs::MapFlags;
+/// # use libbpf_rs::MapHandle;
+/// # use libbpf_rs::TypedMap;
+/// # let get_map = || -> &MapHandle { todo!() };
+/// let map = TypedMap::<LpmKey<4>, u32>::new(get_map()).unwrap();
+/// let key = "10.0.0.0/8".parse::<LpmKey<4>>().unwrap();
+/// let () = map.insert(&key, &1, MapFlags::ANY).unwrap();
+/// assert_eq!(map.longest_match(&[10, 1, 2, 3]).unwrap(), Some(1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(C, packed)]
+pub struct LpmKey<const N: usize> {
+    prefix_len: u32,
+    data: [u8; N],
+}
+
+// SAFETY: `LpmKey` is a `repr(C)` type without padding and any bit
+//         pattern is valid for its fields.
+unsafe impl<const N: usize> Plain for LpmKey<N> {}
+
+// SAFETY: `LpmKey` is a packed type and hence does not contain padding.
+//         Any bit pattern is valid for its fields.
+unsafe impl<const N: usize> NoPadding for LpmKey<N> {}
+
+impl<const N: usize> LpmKey<N> {
+    /// The maximum prefix length supported by this key type.
+    pub const MAX_PREFIX_LEN: u32 = (N * 8) as u32;
+
+    /// Create a new key from the given prefix length and data.
+    ///
+    /// Data bits beyond the prefix are cleared.
+    ///
+    /// # Errors
+    /// If `prefix_len` exceeds [`Self::MAX_PREFIX_LEN`].
+    pub fn new(prefix_len: u32, mut data: [u8; N]) -> Result<Self> {
+        if prefix_len > Self::MAX_PREFIX_LEN {
+            return Err(Error::with_invalid_data(format!(
+                "prefix length {prefix_len} exceeds maximum of {}",
+                Self::MAX_PREFIX_LEN
+            )));
+        }
+
+        for (i, byte) in data.iter_mut().enumerate() {
+            let bits = (prefix_len as usize).saturating_sub(i * 8).min(8);
+            *byte &= (0xff_u16 << (8 - bits)) as u8;
+        }
+
+        Ok(Self { prefix_len, data })
+    }
+
+    /// Create a new key matching exactly `data`, i.e., using the
+    /// maximum prefix length.
+    pub fn host(data: [u8; N]) -> Self {
+        Self {
+            prefix_len: Self::MAX_PREFIX_LEN,
+            data,
+        }
+    }
+
+    /// Retrieve the prefix length.
+    #[inline]
+    pub fn prefix_len(&self) -> u32 {
+        self.prefix_len
+    }
+
+    /// Retrieve the key's data, in network byte order.
+    #[inline]
+    pub fn data(&self) -> [u8; N] {
+        self.data
+    }
+}
+
+impl LpmKey<4> {
+    /// Create a key for the IPv4 network `addr/prefix_len`.
+    pub fn from_ipv4(addr: Ipv4Addr, prefix_len: u32) -> Result<Self> {
+        Self::new(prefix_len, addr.octets())
+    }
+
+    /// Retrieve the network address of the key.
+    pub fn ipv4_addr(&self) -> Ipv4Addr {
+        Ipv4Addr::from(self.data)
+    }
+}
+
+impl LpmKey<16> {
+    /// Create a key for the IPv6 network `addr/prefix_len`.
+    pub fn from_ipv6(addr: Ipv6Addr, prefix_len: u32) -> Result<Self> {
+        Self::new(prefix_len, addr.octets())
+    }
+
+    /// Retrieve the network address of the key.
+    pub fn ipv6_addr(&self) -> Ipv6Addr {
+        Ipv6Addr::from(self.data)
+    }
+}
+
+impl From<Ipv4Addr> for LpmKey<4> {
+    fn from(addr: Ipv4Addr) -> Self {
+        Self::host(addr.octets())
+    }
+}
+
+impl From<Ipv6Addr> for LpmKey<16> {
+    fn from(addr: Ipv6Addr) -> Self {
+        Self::host(addr.octets())
+    }
+}
+
+/// Split a network in CIDR notation into its address and prefix
+/// length, if present.
+fn parse_cidr<A>(s: &str) -> Result<(A, Option<u32>)>
+where
+    A: FromStr,
+{
+    let (addr, prefix_len) = match s.split_once('/') {
+        Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+        None => (s, None),
+    };
+
+    let addr = addr
+        .parse::<A>()
+        .map_err(|_err| Error::with_invalid_data(format!("invalid network address `{addr}`")))?;
+    let prefix_len = prefix_len
+        .map(|prefix_len| {
+            prefix_len.parse::<u32>().map_err(|_err| {
+                Error::with_invalid_data(format!("invalid prefix length `{prefix_len}`"))
+            })
+        })
+        .transpose()?;
+    Ok((addr, prefix_len))
+}
+
+impl FromStr for LpmKey<4> {
+    type Err = Error;
+
+    /// Parse an IPv4 network in CIDR notation, e.g., `10.0.0.0/8`. An
+    /// address without prefix length is interpreted as a host address.
+    fn from_str(s: &str) -> Result<Self> {
+        let (addr, prefix_len) = parse_cidr::<Ipv4Addr>(s)?;
+        Self::from_ipv4(addr, prefix_len.unwrap_or(Self::MAX_PREFIX_LEN))
+    }
+}
+
+impl FromStr for LpmKey<16> {
+    type Err = Error;
+
+    /// Parse an IPv6 network in CIDR notation, e.g., `fd00::/8`. An
+    /// address without prefix length is interpreted as a host address.
+    fn from_str(s: &str) -> Result<Self> {
+        let (addr, prefix_len) = parse_cidr::<Ipv6Addr>(s)?;
+        Self::from_ipv6(addr, prefix_len.unwrap_or(Self::MAX_PREFIX_LEN))
+    }
+}
+
+impl Display for LpmKey<4> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}/{}", self.ipv4_addr(), self.prefix_len())
+    }
+}
+
+impl Display for LpmKey<16> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}/{}", self.ipv6_addr(), self.prefix_len())
+    }
+}
+
+impl<const N: usize, V> TypedMap<'_, LpmKey<N>, V>
+where
+    V: NoPadding,
+{
+    /// Look up the value of the longest prefix stored in the map that
+    /// matches `data`.
+    ///
+    /// Note that [`TypedMap::get`] performs a longest prefix match as
+    /// well, but considers only prefixes up to the key's prefix length.
+    pub fn longest_match(&self, data: &[u8; N]) -> Result<Option<V>> {
+        self.get_with_flags(&LpmKey::host(*data), MapFlags::ANY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::mem::size_of;
+
+    /// Check that `LpmKey` has the layout of `struct bpf_lpm_trie_key`.
+    #[test]
+    fn key_layout() {
+        assert_eq!(size_of::<LpmKey<4>>(), 8);
+        assert_eq!(size_of::<LpmKey<6>>(), 10);
+        assert_eq!(size_of::<LpmKey<16>>(), 20);
+    }
+
+    /// Check that we can parse and format networks in CIDR notation.
+    #[test]
+    fn cidr_roundtrip() {
+        let key = "10.1.2.3/8".parse::<LpmKey<4>>().unwrap();
+        assert_eq!(key.prefix_len(), 8);
+        assert_eq!(key.data(), [10, 0, 0, 0]);
+        assert_eq!(key.to_string(), "10.0.0.0/8");
+
+        let key = "10.1.2.3".parse::<LpmKey<4>>().unwrap();
+        assert_eq!(key, LpmKey::from(Ipv4Addr::new(10, 1, 2, 3)));
+
+        let key = "fd00:1::/20".parse::<LpmKey<16>>().unwrap();
+        assert_eq!(key.to_string(), "fd00::/20");
+
+        assert!("10.0.0.0/33".parse::<LpmKey<4>>().is_err());
+        assert!("10.0.0/8".parse::<LpmKey<4>>().is_err());
+    }
+}
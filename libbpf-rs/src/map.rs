@@ -9,6 +9,7 @@ use std::io;
 use std::marker::PhantomData;
 use std::mem;
 use std::mem::transmute;
+use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::os::unix::ffi::OsStrExt;
 use std::os::unix::io::AsFd;
@@ -34,6 +35,7 @@ use crate::util::validate_bpf_ret;
 use crate::AsRawLibbpf;
 use crate::Error;
 use crate::ErrorExt as _;
+use crate::InnerMapSpec;
 use crate::Link;
 use crate::Mut;
 use crate::Result;
@@ -85,6 +87,35 @@ impl<'obj> OpenMap<'obj> {
         MapType::from(ty)
     }
 
+    /// Retrieve the spec of the inner map of a map-in-map, as defined
+    /// by its BTF `values` template.
+    ///
+    /// Returns `None` if the map is not a map-in-map or no template is
+    /// available. Note that libbpf discards the template once the map
+    /// got created.
+    pub fn inner_map_spec(&self) -> Option<InnerMapSpec> {
+        // SAFETY: `self.ptr` is a valid pointer to a `bpf_map` object.
+        let inner = unsafe { libbpf_sys::bpf_map__inner_map(self.ptr.as_ptr()) };
+        if inner.is_null() {
+            return None
+        }
+
+        // SAFETY: libbpf returns either NULL or a valid pointer to the
+        //         inner map, which lives as long as the outer map and we
+        //         checked for NULL above.
+        let spec = unsafe {
+            InnerMapSpec {
+                map_type: MapType::from(libbpf_sys::bpf_map__type(inner)),
+                key_size: libbpf_sys::bpf_map__key_size(inner),
+                value_size: libbpf_sys::bpf_map__value_size(inner),
+                max_entries: libbpf_sys::bpf_map__max_entries(inner),
+                map_flags: libbpf_sys::bpf_map__map_flags(inner),
+                _non_exhaustive: (),
+            }
+        };
+        Some(spec)
+    }
+
     fn initial_value_raw(&self) -> (*mut u8, usize) {
         let mut size = 0u64;
         let ptr = unsafe {
@@ -298,8 +329,17 @@ where
     key.as_ptr() as *const c_void
 }
 
-/// Internal function to return a value from a map into a buffer of the given size.
-fn lookup_raw<M>(map: &M, key: &[u8], flags: MapFlags, out_size: usize) -> Result<Option<Vec<u8>>>
+/// Internal function to look up a value from a map into the provided
+/// buffer. Returns `true` if the value was found.
+///
+/// The caller has to make sure that `out` is large enough to hold the
+/// value.
+fn lookup_raw_into<M>(
+    map: &M,
+    key: &[u8],
+    flags: MapFlags,
+    out: &mut [MaybeUninit<u8>],
+) -> Result<bool>
 where
     M: MapCore + ?Sized,
 {
@@ -311,8 +351,6 @@ where
         )));
     };
 
-    let mut out: Vec<u8> = Vec::with_capacity(out_size);
-
     let ret = unsafe {
         libbpf_sys::bpf_map_lookup_elem_flags(
             map.as_fd().as_raw_fd(),
@@ -323,20 +361,34 @@ where
     };
 
     if ret == 0 {
-        unsafe {
-            out.set_len(out_size);
-        }
-        Ok(Some(out))
+        Ok(true)
     } else {
         let err = io::Error::last_os_error();
         if err.kind() == io::ErrorKind::NotFound {
-            Ok(None)
+            Ok(false)
         } else {
             Err(Error::from(err))
         }
     }
 }
 
+/// Internal function to return a value from a map into a buffer of the given size.
+fn lookup_raw<M>(map: &M, key: &[u8], flags: MapFlags, out_size: usize) -> Result<Option<Vec<u8>>>
+where
+    M: MapCore + ?Sized,
+{
+    let mut out: Vec<u8> = Vec::with_capacity(out_size);
+
+    if lookup_raw_into(map, key, flags, &mut out.spare_capacity_mut()[..out_size])? {
+        unsafe {
+            out.set_len(out_size);
+        }
+        Ok(Some(out))
+    } else {
+        Ok(None)
+    }
+}
+
 /// Internal function to update a map. This does not check the length of the
 /// supplied value.
 fn update_raw<M>(map: &M, key: &[u8], value: &[u8], flags: MapFlags) -> Result<()>
@@ -370,7 +422,7 @@ fn lookup_batch_raw<M>(
     elem_flags: MapFlags,
     flags: MapFlags,
     delete: bool,
-) -> BatchedMapIter<'_>
+) -> Result<BatchedMapIter<'_>>
 where
     M: MapCore + ?Sized,
 {
@@ -391,7 +443,37 @@ where
         map.key_size()
     };
 
-    BatchedMapIter::new(map.as_fd(), count, key_size, map.value_size(), opts, delete)
+    // for per-cpu maps the kernel reports one value per possible CPU,
+    // each rounded up to eight bytes
+    let value_size = if map.map_type().is_percpu() {
+        percpu_buffer_size(map)? as u32
+    } else {
+        map.value_size()
+    };
+
+    Ok(BatchedMapIter::new(
+        map.as_fd(),
+        count,
+        key_size,
+        value_size,
+        opts,
+        delete,
+    ))
+}
+
+/// Internal function that returns an error for maps that are not per-cpu.
+fn check_percpu<M>(map: &M) -> Result<()>
+where
+    M: MapCore + ?Sized,
+{
+    if !map.map_type().is_percpu() && map.map_type() != MapType::Unknown {
+        return Err(Error::with_invalid_data(format!(
+            "lookup() must be used for maps that are not per-cpu (type of the map is {:?})",
+            map.map_type(),
+        )));
+    }
+
+    Ok(())
 }
 
 /// Intneral function that returns an error for per-cpu and bloom filter maps.
@@ -453,6 +535,14 @@ pub trait MapCore: Debug + AsFd + private::Sealed {
         MapKeyIter::new(self.as_fd(), self.key_size())
     }
 
+    /// Returns a cursor over keys in this map that reuses a single key
+    /// buffer instead of allocating one per key.
+    ///
+    /// The same caveats as for [`Self::keys()`] apply.
+    fn key_cursor(&self) -> MapKeyCursor<'_> {
+        MapKeyCursor::new(self.as_fd(), self.key_size())
+    }
+
     /// Returns map value as `Vec` of `u8`.
     ///
     /// `key` must have exactly [`Self::key_size()`] elements.
@@ -466,6 +556,67 @@ pub trait MapCore: Debug + AsFd + private::Sealed {
         lookup_raw(self, key, flags, out_size)
     }
 
+    /// Looks up a map value, writing it into the provided buffer.
+    ///
+    /// `key` must have exactly [`Self::key_size()`] elements and `value`
+    /// exactly [`Self::value_size()`] elements. Returns `true` if a value
+    /// for `key` was found, in which case it was written into `value`, and
+    /// `false` otherwise.
+    ///
+    /// This is the allocation free equivalent of [`Self::lookup()`] and the
+    /// same restrictions concerning map types apply.
+    fn lookup_into(&self, key: &[u8], value: &mut [u8], flags: MapFlags) -> Result<bool> {
+        check_not_bloom_or_percpu(self)?;
+        if value.len() != self.value_size() as usize {
+            return Err(Error::with_invalid_data(format!(
+                "value_size {} != {}",
+                value.len(),
+                self.value_size()
+            )));
+        };
+
+        // SAFETY: `MaybeUninit<u8>` has the same layout as `u8` and the
+        //         kernel only ever writes initialized bytes.
+        let value = unsafe {
+            slice::from_raw_parts_mut(value.as_mut_ptr().cast::<MaybeUninit<u8>>(), value.len())
+        };
+        lookup_raw_into(self, key, flags, value)
+    }
+
+    /// Looks up a map value, writing it into the provided, potentially
+    /// uninitialized, buffer.
+    ///
+    /// `key` must have exactly [`Self::key_size()`] elements and `value`
+    /// exactly [`Self::value_size()`] elements. If a value for `key` was
+    /// found, the now initialized buffer is returned.
+    ///
+    /// This is the allocation free equivalent of [`Self::lookup()`] and the
+    /// same restrictions concerning map types apply.
+    fn lookup_uninit<'buf>(
+        &self,
+        key: &[u8],
+        value: &'buf mut [MaybeUninit<u8>],
+        flags: MapFlags,
+    ) -> Result<Option<&'buf mut [u8]>> {
+        check_not_bloom_or_percpu(self)?;
+        if value.len() != self.value_size() as usize {
+            return Err(Error::with_invalid_data(format!(
+                "value_size {} != {}",
+                value.len(),
+                self.value_size()
+            )));
+        };
+
+        if lookup_raw_into(self, key, flags, value)? {
+            // SAFETY: The kernel initialized the entire buffer.
+            let value =
+                unsafe { slice::from_raw_parts_mut(value.as_mut_ptr().cast::<u8>(), value.len()) };
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Returns many elements in batch mode from the map.
     ///
     /// `count` specifies the batch size.
@@ -476,7 +627,7 @@ pub trait MapCore: Debug + AsFd + private::Sealed {
         flags: MapFlags,
     ) -> Result<BatchedMapIter<'_>> {
         check_not_bloom_or_percpu(self)?;
-        Ok(lookup_batch_raw(self, count, elem_flags, flags, false))
+        lookup_batch_raw(self, count, elem_flags, flags, false)
     }
 
     /// Returns many elements in batch mode from the map.
@@ -489,7 +640,38 @@ pub trait MapCore: Debug + AsFd + private::Sealed {
         flags: MapFlags,
     ) -> Result<BatchedMapIter<'_>> {
         check_not_bloom_or_percpu(self)?;
-        Ok(lookup_batch_raw(self, count, elem_flags, flags, true))
+        lookup_batch_raw(self, count, elem_flags, flags, true)
+    }
+
+    /// Returns many elements in batch mode from a per-cpu map.
+    ///
+    /// `count` specifies the batch size. Each value reported by the
+    /// returned iterator contains the values for all possible CPUs, in
+    /// the layout expected by [`PerCpuValues::from_raw`][crate::PerCpuValues::from_raw].
+    fn lookup_percpu_batch(
+        &self,
+        count: u32,
+        elem_flags: MapFlags,
+        flags: MapFlags,
+    ) -> Result<BatchedMapIter<'_>> {
+        check_percpu(self)?;
+        lookup_batch_raw(self, count, elem_flags, flags, false)
+    }
+
+    /// Returns and deletes many elements in batch mode from a per-cpu
+    /// map.
+    ///
+    /// `count` specifies the batch size. Each value reported by the
+    /// returned iterator contains the values for all possible CPUs, in
+    /// the layout expected by [`PerCpuValues::from_raw`][crate::PerCpuValues::from_raw].
+    fn lookup_and_delete_percpu_batch(
+        &self,
+        count: u32,
+        elem_flags: MapFlags,
+        flags: MapFlags,
+    ) -> Result<BatchedMapIter<'_>> {
+        check_percpu(self)?;
+        lookup_batch_raw(self, count, elem_flags, flags, true)
     }
 
     /// Returns if the given value is likely present in bloom_filter as `bool`.
@@ -520,12 +702,7 @@ pub trait MapCore: Debug + AsFd + private::Sealed {
     ///
     /// For normal maps, [`Self::lookup()`] must be used.
     fn lookup_percpu(&self, key: &[u8], flags: MapFlags) -> Result<Option<Vec<Vec<u8>>>> {
-        if !self.map_type().is_percpu() && self.map_type() != MapType::Unknown {
-            return Err(Error::with_invalid_data(format!(
-                "lookup() must be used for maps that are not per-cpu (type of the map is {:?})",
-                self.map_type(),
-            )));
-        }
+        check_percpu(self)?;
 
         let val_size = self.value_size() as usize;
         let aligned_val_size = percpu_aligned_value_size(self);
@@ -543,6 +720,73 @@ pub trait MapCore: Debug + AsFd + private::Sealed {
         }
     }
 
+    /// Looks up the per-cpu values of a per-cpu map, writing them into
+    /// the provided contiguous buffer.
+    ///
+    /// `key` must have exactly [`Self::key_size()`] elements. `values`
+    /// must hold one value per possible cpu (see
+    /// [`num_possible_cpus`][crate::num_possible_cpus]), with each value
+    /// being [`Self::value_size()`] bytes large and rounded up to a
+    /// multiple of eight bytes, as per the kernel's layout. Returns `true`
+    /// if a value for `key` was found.
+    ///
+    /// This is the allocation free equivalent of [`Self::lookup_percpu()`].
+    fn lookup_percpu_into(&self, key: &[u8], values: &mut [u8], flags: MapFlags) -> Result<bool> {
+        check_percpu(self)?;
+
+        let buf_size = percpu_buffer_size(self)?;
+        if values.len() != buf_size {
+            return Err(Error::with_invalid_data(format!(
+                "per-cpu buffer size {} != {}",
+                values.len(),
+                buf_size
+            )));
+        }
+
+        // SAFETY: `MaybeUninit<u8>` has the same layout as `u8` and the
+        //         kernel only ever writes initialized bytes.
+        let values = unsafe {
+            slice::from_raw_parts_mut(values.as_mut_ptr().cast::<MaybeUninit<u8>>(), values.len())
+        };
+        lookup_raw_into(self, key, flags, values)
+    }
+
+    /// Looks up the per-cpu values of a per-cpu map, writing them into
+    /// the provided, potentially uninitialized, contiguous buffer.
+    ///
+    /// The same layout requirements as for [`Self::lookup_percpu_into()`]
+    /// apply to `values`. If a value for `key` was found, the now
+    /// initialized buffer is returned.
+    fn lookup_percpu_uninit<'buf>(
+        &self,
+        key: &[u8],
+        values: &'buf mut [MaybeUninit<u8>],
+        flags: MapFlags,
+    ) -> Result<Option<&'buf mut [u8]>> {
+        check_percpu(self)?;
+
+        let buf_size = percpu_buffer_size(self)?;
+        if values.len() != buf_size {
+            return Err(Error::with_invalid_data(format!(
+                "per-cpu buffer size {} != {}",
+                values.len(),
+                buf_size
+            )));
+        }
+
+        if lookup_raw_into(self, key, flags, values)? {
+            // SAFETY: The kernel initialized the entire buffer, including
+            //         the padding of each value up to a multiple of eight
+            //         bytes.
+            let values = unsafe {
+                slice::from_raw_parts_mut(values.as_mut_ptr().cast::<u8>(), values.len())
+            };
+            Ok(Some(values))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Deletes an element from the map.
     ///
     /// `key` must have exactly [`Self::key_size()`] elements.
@@ -1356,6 +1600,55 @@ impl Iterator for MapKeyIter<'_> {
     }
 }
 
+/// A cursor over the keys of a BPF map.
+///
+/// Contrary to [`MapKeyIter`], a `MapKeyCursor` does not allocate
+/// memory for every key it visits, but hands out references to an
+/// internal buffer instead.
+#[derive(Debug)]
+pub struct MapKeyCursor<'map> {
+    map_fd: BorrowedFd<'map>,
+    started: bool,
+    prev: Vec<u8>,
+    next: Vec<u8>,
+}
+
+impl<'map> MapKeyCursor<'map> {
+    fn new(map_fd: BorrowedFd<'map>, key_size: u32) -> Self {
+        Self {
+            map_fd,
+            started: false,
+            prev: vec![0; key_size as usize],
+            next: vec![0; key_size as usize],
+        }
+    }
+
+    /// Advance the cursor, returning the next key or `None` once all
+    /// keys have been visited.
+    pub fn next_key(&mut self) -> Option<&[u8]> {
+        let prev = if self.started {
+            self.prev.as_ptr()
+        } else {
+            ptr::null()
+        };
+
+        let ret = unsafe {
+            libbpf_sys::bpf_map_get_next_key(
+                self.map_fd.as_raw_fd(),
+                prev as _,
+                self.next.as_mut_ptr() as _,
+            )
+        };
+        if ret != 0 {
+            None
+        } else {
+            let () = mem::swap(&mut self.prev, &mut self.next);
+            self.started = true;
+            Some(&self.prev)
+        }
+    }
+}
+
 /// An iterator over batches of key value pairs of a BPF map.
 #[derive(Debug)]
 pub struct BatchedMapIter<'map> {
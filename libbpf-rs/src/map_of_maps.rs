@@ -0,0 +1,182 @@
+use std::ffi::OsStr;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::os::unix::io::AsRawFd as _;
+use std::os::unix::io::BorrowedFd;
+
+use crate::util;
+use crate::Error;
+use crate::MapCore;
+use crate::MapFlags;
+use crate::MapHandle;
+use crate::MapType;
+use crate::NoPadding;
+use crate::Result;
+
+/// The shape of an inner map of a map-in-map.
+///
+/// The kernel requires all inner maps stored in an outer map to be
+/// compatible with the inner map the outer map was created with. An
+/// `InnerMapSpec` captures the relevant properties and can be used to
+/// create compatible inner maps.
+///
+/// A spec can be retrieved from the BTF `values` template of a not yet
+/// loaded outer map using [`OpenMap::inner_map_spec`][crate::OpenMap::inner_map_spec]
+/// or from an existing inner map using [`InnerMapSpec::from_map`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InnerMapSpec {
+    /// The type of the inner map.
+    pub map_type: MapType,
+    /// The key size of the inner map.
+    pub key_size: u32,
+    /// The value size of the inner map.
+    pub value_size: u32,
+    /// The maximum number of entries of the inner map.
+    pub max_entries: u32,
+    /// The flags the inner map is created with.
+    pub map_flags: u32,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl InnerMapSpec {
+    /// Retrieve the spec of an existing map.
+    pub fn from_map(map: &dyn MapCore) -> Result<Self> {
+        let info = map.info()?.info;
+        Ok(Self {
+            map_type: MapType::from(info.type_),
+            key_size: info.key_size,
+            value_size: info.value_size,
+            max_entries: info.max_entries,
+            map_flags: info.map_flags,
+            _non_exhaustive: (),
+        })
+    }
+
+    /// Create a new map matching this spec.
+    pub fn create<T: AsRef<OsStr>>(&self, name: Option<T>) -> Result<MapHandle> {
+        let opts = libbpf_sys::bpf_map_create_opts {
+            sz: size_of::<libbpf_sys::bpf_map_create_opts>() as _,
+            map_flags: self.map_flags,
+            ..Default::default()
+        };
+
+        MapHandle::create(
+            self.map_type,
+            name,
+            self.key_size,
+            self.value_size,
+            self.max_entries,
+            &opts,
+        )
+    }
+}
+
+/// A view on a [`MapType::ArrayOfMaps`] or [`MapType::HashOfMaps`] map
+/// with keys of type `K`.
+///
+/// Inner maps are inserted by file descriptor. Lookups from user
+/// space report the inner map's ID, which can be used to open the
+/// inner map.
+///
+/// Updating an entry replaces the inner map atomically from the
+/// perspective of BPF programs, which makes this type suitable for,
+/// say, double-buffering configuration tables.
+pub struct MapOfMaps<'map, K> {
+    map: &'map dyn MapCore,
+    _phantom: PhantomData<fn(&K)>,
+}
+
+impl<'map, K> MapOfMaps<'map, K>
+where
+    K: NoPadding,
+{
+    /// Create a new `MapOfMaps` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::ArrayOfMaps`] or
+    ///   [`MapType::HashOfMaps`].
+    /// * If the map's key size does not match the size of `K` or its
+    ///   value is not four bytes large.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let map_type = map.map_type();
+        if !matches!(map_type, MapType::ArrayOfMaps | MapType::HashOfMaps) {
+            return Err(Error::with_invalid_data(format!(
+                "map type {map_type:?} is not a map-in-map"
+            )));
+        }
+
+        if map.key_size() as usize != size_of::<K>() {
+            return Err(Error::with_invalid_data(format!(
+                "key_size {} != {}",
+                map.key_size(),
+                size_of::<K>()
+            )));
+        }
+
+        if map.value_size() as usize != size_of::<u32>() {
+            return Err(Error::with_invalid_data(format!(
+                "value_size {} != {}",
+                map.value_size(),
+                size_of::<u32>()
+            )));
+        }
+
+        Ok(Self {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Insert or replace the inner map associated with `key`.
+    pub fn insert(&self, key: &K, inner: BorrowedFd<'_>, flags: MapFlags) -> Result<()> {
+        let fd = inner.as_raw_fd() as u32;
+        self.map.update(util::as_bytes(key), &fd.to_ne_bytes(), flags)
+    }
+
+    /// Look up the ID of the inner map associated with `key`.
+    pub fn get_id(&self, key: &K) -> Result<Option<u32>> {
+        let mut id = [0; size_of::<u32>()];
+        let found = self.map.lookup_into(util::as_bytes(key), &mut id, MapFlags::ANY)?;
+        Ok(found.then(|| u32::from_ne_bytes(id)))
+    }
+
+    /// Look up and open the inner map associated with `key`.
+    pub fn get(&self, key: &K) -> Result<Option<MapHandle>> {
+        self.get_id(key)?.map(MapHandle::from_map_id).transpose()
+    }
+
+    /// Replace the inner map associated with `key`, returning the
+    /// previously stored inner map, if any.
+    ///
+    /// BPF programs observe the replacement atomically. Note, however,
+    /// that the retrieval of the previous inner map and the update are
+    /// separate operations and concurrent updates from user space may
+    /// interleave.
+    pub fn swap(&self, key: &K, inner: BorrowedFd<'_>) -> Result<Option<MapHandle>> {
+        let prev = self.get(key)?;
+        let () = self.insert(key, inner, MapFlags::ANY)?;
+        Ok(prev)
+    }
+
+    /// Remove the inner map associated with `key`.
+    pub fn remove(&self, key: &K) -> Result<()> {
+        self.map.delete(util::as_bytes(key))
+    }
+}
+
+impl<K> Debug for MapOfMaps<'_, K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map, _phantom } = self;
+        f.debug_struct("MapOfMaps").field("map", map).finish()
+    }
+}
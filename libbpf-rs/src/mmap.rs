@@ -0,0 +1,297 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::os::unix::io::AsFd as _;
+use std::os::unix::io::AsRawFd as _;
+use std::ptr;
+use std::ptr::NonNull;
+use std::slice;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::AtomicU64;
+
+use plain::Plain;
+
+use crate::util;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::MapCore;
+use crate::MapType;
+use crate::Result;
+
+/// A memory mapped [`MapType::Array`] map.
+///
+/// Array maps created with the `BPF_F_MMAPABLE` flag can be mapped into
+/// the address space of the process, allowing to access their values
+/// without issuing a system call for each access. The mapping is
+/// read-only if the map is frozen or not writable from user space and
+/// read-write otherwise.
+///
+/// `T` is the type of a single value of the array. Values are laid out
+/// at a stride of their size rounded up to eight bytes.
+///
+/// The mapping borrows the map it was created from, ensuring that the
+/// map's file descriptor stays alive for as long as the mapping exists.
+pub struct MmapArray<'map, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    stride: usize,
+    mmap_len: usize,
+    writable: bool,
+    _map: PhantomData<&'map dyn MapCore>,
+}
+
+impl<'map, T> MmapArray<'map, T>
+where
+    T: Plain,
+{
+    /// Memory map the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not a [`MapType::Array`] created with the
+    ///   `BPF_F_MMAPABLE` flag.
+    /// * If the map's value size does not match the size of `T`.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let map_type = map.map_type();
+        if map_type != MapType::Array {
+            return Err(Error::with_invalid_data(format!(
+                "map type {map_type:?} cannot be memory mapped"
+            )));
+        }
+
+        let info = map.info()?.info;
+        if info.map_flags & libbpf_sys::BPF_F_MMAPABLE == 0 {
+            return Err(Error::with_invalid_data(
+                "map was not created with the BPF_F_MMAPABLE flag",
+            ));
+        }
+
+        if map.value_size() as usize != size_of::<T>() {
+            return Err(Error::with_invalid_data(format!(
+                "value_size {} != {}",
+                map.value_size(),
+                size_of::<T>()
+            )));
+        }
+
+        let len = info.max_entries as usize;
+        let stride = util::roundup(size_of::<T>(), 8);
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let mmap_len = util::roundup(stride * len, page_size);
+        let fd = map.as_fd().as_raw_fd();
+
+        let mmap = |prot| unsafe {
+            let ptr = libc::mmap(ptr::null_mut(), mmap_len, prot, libc::MAP_SHARED, fd, 0);
+            if ptr == libc::MAP_FAILED {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(ptr)
+            }
+        };
+
+        // The kernel refuses writable mappings of frozen maps and of
+        // maps we only have read access to. Fall back to a read-only
+        // mapping in that case.
+        let (ptr, writable) = match mmap(libc::PROT_READ | libc::PROT_WRITE) {
+            Ok(ptr) => (ptr, true),
+            Err(err) if matches!(err.raw_os_error(), Some(libc::EPERM) | Some(libc::EACCES)) => {
+                let ptr = mmap(libc::PROT_READ)
+                    .map_err(Error::from)
+                    .context("failed to memory map BPF map")?;
+                (ptr, false)
+            }
+            Err(err) => return Err(Error::from(err)).context("failed to memory map BPF map"),
+        };
+
+        Ok(Self {
+            // SAFETY: `mmap` never returns NULL on success.
+            ptr: unsafe { NonNull::new_unchecked(ptr.cast()) },
+            len,
+            stride,
+            mmap_len,
+            writable,
+            _map: PhantomData,
+        })
+    }
+
+    /// Retrieve a pointer to the value at `index`, which has to be in
+    /// bounds.
+    fn value_ptr(&self, index: usize) -> *mut T {
+        debug_assert!(index < self.len);
+        // SAFETY: `index` is in bounds and so the resulting pointer
+        //         stays within the mapping.
+        unsafe {
+            self.ptr
+                .as_ptr()
+                .cast::<u8>()
+                .add(index * self.stride)
+                .cast()
+        }
+    }
+
+    /// Retrieve the number of values in the array.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the array is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Retrieve the distance between two consecutive values, in bytes.
+    ///
+    /// The stride is the size of `T` rounded up to eight bytes.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Check whether the mapping is writable.
+    ///
+    /// Mappings of frozen maps are always read-only.
+    #[inline]
+    pub fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    /// Read the value at `index`.
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None
+        }
+
+        // SAFETY: `index` is in bounds and the memory is mapped for as
+        //         long as `self` exists. We use a volatile read, as
+        //         the value may be modified concurrently by BPF
+        //         programs.
+        Some(unsafe { ptr::read_volatile(self.value_ptr(index)) })
+    }
+
+    /// Write `value` at `index`.
+    ///
+    /// # Errors
+    /// * If `index` is out of bounds.
+    /// * If the mapping is read-only.
+    pub fn set(&self, index: usize, value: T) -> Result<()> {
+        if index >= self.len {
+            return Err(Error::with_invalid_data(format!(
+                "index {index} is out of bounds for array of length {}",
+                self.len
+            )));
+        }
+
+        if !self.writable {
+            return Err(Error::with_io_error(
+                io::ErrorKind::PermissionDenied,
+                "memory mapped map is read-only",
+            ));
+        }
+
+        // SAFETY: `index` is in bounds and the memory is mapped
+        //         writable for as long as `self` exists.
+        let () = unsafe { ptr::write_volatile(self.value_ptr(index), value) };
+        Ok(())
+    }
+
+    /// Retrieve a raw pointer to the first value of the array.
+    ///
+    /// Subsequent values are located at multiples of
+    /// [`stride`][Self::stride] bytes from it.
+    #[inline]
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Retrieve the array's values as a slice.
+    ///
+    /// # Errors
+    /// If the array contains more than one value and the size of `T` is
+    /// not a multiple of eight bytes, in which case values are not laid
+    /// out contiguously.
+    ///
+    /// # Safety
+    /// The values may be modified concurrently by BPF programs or other
+    /// users of the map. The caller has to make sure that no such
+    /// modification happens for as long as the returned slice exists.
+    pub unsafe fn as_slice(&self) -> Result<&[T]> {
+        if self.len > 1 && self.stride != size_of::<T>() {
+            return Err(Error::with_invalid_data(format!(
+                "values of size {} are not laid out contiguously",
+                size_of::<T>()
+            )));
+        }
+
+        // SAFETY: The memory is mapped for as long as `self` exists,
+        //         values are contiguous, and the caller guarantees the
+        //         absence of concurrent modification.
+        let values = unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) };
+        Ok(values)
+    }
+}
+
+macro_rules! impl_as_atomic {
+    ($ty:ty, $atomic:ty) => {
+        impl MmapArray<'_, $ty> {
+            /// Retrieve the array's values as a slice of atomics.
+            ///
+            /// Atomics can be used to safely share values with BPF
+            /// programs accessing them concurrently, e.g., using
+            /// `__sync_fetch_and_add`.
+            ///
+            /// # Errors
+            /// * If the mapping is read-only.
+            pub fn as_atomic(&self) -> Result<&[$atomic]> {
+                if !self.writable {
+                    return Err(Error::with_io_error(
+                        io::ErrorKind::PermissionDenied,
+                        "memory mapped map is read-only",
+                    ));
+                }
+
+                // SAFETY: The atomic type has the same in-memory
+                //         representation as the integer type, values
+                //         of eight bytes are laid out contiguously,
+                //         the memory is page aligned, and it is mapped
+                //         writable for as long as `self` exists.
+                let atomics =
+                    unsafe { slice::from_raw_parts(self.ptr.as_ptr().cast::<$atomic>(), self.len) };
+                Ok(atomics)
+            }
+        }
+    };
+}
+
+impl_as_atomic!(u64, AtomicU64);
+impl_as_atomic!(i64, AtomicI64);
+
+impl<T> Debug for MmapArray<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            ptr,
+            len,
+            stride,
+            mmap_len,
+            writable,
+            _map,
+        } = self;
+
+        f.debug_struct("MmapArray")
+            .field("ptr", ptr)
+            .field("len", len)
+            .field("stride", stride)
+            .field("mmap_len", mmap_len)
+            .field("writable", writable)
+            .finish()
+    }
+}
+
+impl<T> Drop for MmapArray<'_, T> {
+    fn drop(&mut self) {
+        let _ret = unsafe { libc::munmap(self.ptr.as_ptr().cast(), self.mmap_len) };
+    }
+}
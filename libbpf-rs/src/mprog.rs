@@ -0,0 +1,169 @@
+use std::mem::size_of;
+use std::ptr;
+
+use crate::util;
+use crate::ErrorExt as _;
+use crate::Result;
+
+/// A program or link that another program is attached relative to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MprogAnchor {
+    /// The program referred to by the given file descriptor.
+    ProgFd(i32),
+    /// The program with the given ID.
+    ProgId(u32),
+    /// The link referred to by the given file descriptor.
+    LinkFd(i32),
+    /// The link with the given ID.
+    LinkId(u32),
+}
+
+/// The position at which to insert a program into the ordered list of
+/// programs of a multi-program attachment point.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MprogPosition {
+    /// Append the program after all other programs.
+    #[default]
+    Last,
+    /// Prepend the program before all other programs.
+    First,
+    /// Insert the program directly before the given program or link.
+    Before(MprogAnchor),
+    /// Insert the program directly after the given program or link.
+    After(MprogAnchor),
+}
+
+impl MprogPosition {
+    /// Convert the position into the `flags`, `relative_fd`, and
+    /// `relative_id` attachment options understood by libbpf.
+    pub(crate) fn to_raw(self) -> (u32, i32, u32) {
+        let (flags, anchor) = match self {
+            Self::Last => (0, None),
+            Self::First => (libbpf_sys::BPF_F_BEFORE, None),
+            Self::Before(anchor) => (libbpf_sys::BPF_F_BEFORE, Some(anchor)),
+            Self::After(anchor) => (libbpf_sys::BPF_F_AFTER, Some(anchor)),
+        };
+
+        // libbpf sets `BPF_F_ID` itself if a relative ID is provided.
+        match anchor {
+            None => (flags, 0, 0),
+            Some(MprogAnchor::ProgFd(fd)) => (flags, fd, 0),
+            Some(MprogAnchor::ProgId(id)) => (flags, 0, id),
+            Some(MprogAnchor::LinkFd(fd)) => (flags | libbpf_sys::BPF_F_LINK, fd, 0),
+            Some(MprogAnchor::LinkId(id)) => (flags | libbpf_sys::BPF_F_LINK, 0, id),
+        }
+    }
+}
+
+/// A program attached to a multi-program attachment point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MprogEntry {
+    /// The ID of the program.
+    pub prog_id: u32,
+    /// The ID of the link the program is attached with, if any.
+    pub link_id: Option<u32>,
+}
+
+/// The programs attached to a multi-program attachment point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MprogProgs {
+    /// The revision of the attachment point, which changes with every
+    /// modification of the program list. It can be used as expected
+    /// revision when attaching programs, to detect concurrent
+    /// modifications.
+    pub revision: u64,
+    /// The attached programs, in execution order.
+    pub progs: Vec<MprogEntry>,
+}
+
+/// The raw result of querying the programs attached to an attachment
+/// point.
+pub(crate) struct ProgQuery {
+    /// The attach flags of the attachment point.
+    pub(crate) attach_flags: u32,
+    /// The revision of the attachment point.
+    pub(crate) revision: u64,
+    /// The IDs of the attached programs.
+    pub(crate) prog_ids: Vec<u32>,
+    /// The IDs of the links the programs are attached with, or zero for
+    /// programs not attached with a link. Empty unless requested.
+    pub(crate) link_ids: Vec<u32>,
+}
+
+/// Query the programs attached to the attachment point `target` for
+/// the given attach type, retrieving link IDs as well if `with_link_ids`
+/// is set.
+pub(crate) fn query_progs(
+    target: i32,
+    attach_type: u32,
+    query_flags: u32,
+    with_link_ids: bool,
+) -> Result<ProgQuery> {
+    let mut prog_ids = Vec::<u32>::new();
+    let mut link_ids = Vec::<u32>::new();
+    loop {
+        let ids_ptr = |ids: &mut Vec<u32>| {
+            if ids.is_empty() {
+                ptr::null_mut()
+            } else {
+                ids.as_mut_ptr()
+            }
+        };
+        let mut opts = libbpf_sys::bpf_prog_query_opts {
+            sz: size_of::<libbpf_sys::bpf_prog_query_opts>() as _,
+            query_flags,
+            prog_ids: ids_ptr(&mut prog_ids),
+            link_ids: ids_ptr(&mut link_ids),
+            __bindgen_anon_1: libbpf_sys::bpf_prog_query_opts__bindgen_ty_1 {
+                count: prog_ids.len() as _,
+            },
+            ..Default::default()
+        };
+        let ret = unsafe { libbpf_sys::bpf_prog_query_opts(target, attach_type, &mut opts) };
+        let cnt = unsafe { opts.__bindgen_anon_1.count } as usize;
+        // The kernel reports the total number of attached programs,
+        // which may have grown since we sized the buffers, in which
+        // case it fails with ENOSPC.
+        if ret == -libc::ENOSPC || cnt > prog_ids.len() {
+            let () = prog_ids.resize(cnt, 0);
+            if with_link_ids {
+                let () = link_ids.resize(cnt, 0);
+            }
+            continue
+        }
+        let () = util::parse_ret(ret)?;
+        let () = prog_ids.truncate(cnt);
+        let () = link_ids.truncate(cnt);
+
+        break Ok(ProgQuery {
+            attach_flags: opts.attach_flags,
+            revision: opts.revision,
+            prog_ids,
+            link_ids,
+        })
+    }
+}
+
+/// Query the programs attached to the attachment point `target` for
+/// the given attach type.
+pub(crate) fn query(target: i32, attach_type: u32) -> Result<MprogProgs> {
+    let ProgQuery {
+        revision,
+        prog_ids,
+        link_ids,
+        ..
+    } = query_progs(target, attach_type, 0, true).context("failed to query attached programs")?;
+
+    let progs = prog_ids
+        .into_iter()
+        .zip(link_ids)
+        .map(|(prog_id, link_id)| MprogEntry {
+            prog_id,
+            link_id: (link_id != 0).then_some(link_id),
+        })
+        .collect();
+
+    Ok(MprogProgs { revision, progs })
+}
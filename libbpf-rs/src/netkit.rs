@@ -0,0 +1,37 @@
+use std::mem::size_of;
+
+use crate::MprogPosition;
+
+/// Options to be provided when attaching a program to a netkit device.
+#[derive(Clone, Debug, Default)]
+pub struct NetkitOpts {
+    /// The position at which to insert the program relative to the
+    /// programs already attached.
+    pub position: MprogPosition,
+    /// If non-zero, only attach the program if the revision of the
+    /// attachment point matches.
+    pub expected_revision: u64,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl From<NetkitOpts> for libbpf_sys::bpf_netkit_opts {
+    fn from(opts: NetkitOpts) -> Self {
+        let NetkitOpts {
+            position,
+            expected_revision,
+            _non_exhaustive,
+        } = opts;
+        let (flags, relative_fd, relative_id) = position.to_raw();
+
+        #[allow(clippy::needless_update)]
+        libbpf_sys::bpf_netkit_opts {
+            sz: size_of::<Self>() as _,
+            flags,
+            relative_fd,
+            relative_id,
+            expected_revision,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ops::Index;
+use std::ops::IndexMut;
+use std::slice;
+
+use crate::map::BatchedMapIter;
+use crate::typed_map::check_map_layout;
+use crate::util;
+use crate::Error;
+use crate::MapCore;
+use crate::MapFlags;
+use crate::MapType;
+use crate::NoPadding;
+use crate::Result;
+
+/// The values of a per-cpu map entry, one for each possible CPU.
+///
+/// The kernel reports values of per-cpu maps (such as
+/// [`MapType::PercpuHash`], [`MapType::PercpuArray`], and
+/// [`MapType::LruPercpuHash`]) as a contiguous buffer containing one
+/// value per possible CPU, with each value rounded up to a multiple of
+/// eight bytes. `PerCpuValues` takes care of this layout and provides
+/// access to the individual values by CPU ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PerCpuValues<T> {
+    values: Vec<T>,
+}
+
+impl<T> PerCpuValues<T> {
+    /// Create a `PerCpuValues` object from a vector containing exactly
+    /// one value per possible CPU.
+    pub fn from_vec(values: Vec<T>) -> Result<Self> {
+        let num_cpus = util::num_possible_cpus()?;
+        if values.len() != num_cpus {
+            return Err(Error::with_invalid_data(format!(
+                "number of values {} != number of possible CPUs {num_cpus}",
+                values.len()
+            )));
+        }
+        Ok(Self { values })
+    }
+
+    /// Retrieve the number of CPUs, i.e., the number of values.
+    #[inline]
+    pub fn num_cpus(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Retrieve the value for the CPU with ID `cpu`.
+    #[inline]
+    pub fn get(&self, cpu: usize) -> Option<&T> {
+        self.values.get(cpu)
+    }
+
+    /// Retrieve a mutable reference to the value for the CPU with ID
+    /// `cpu`.
+    #[inline]
+    pub fn get_mut(&mut self, cpu: usize) -> Option<&mut T> {
+        self.values.get_mut(cpu)
+    }
+
+    /// Returns an iterator over the values of all possible CPUs, in
+    /// order of CPU ID.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.values.iter()
+    }
+
+    /// Returns an iterator over the values of CPUs that are currently
+    /// online, along with their CPU ID.
+    pub fn iter_online(&self) -> Result<impl Iterator<Item = (usize, &T)> + '_> {
+        let cpus = util::online_cpus()?;
+        let iter = cpus
+            .into_iter()
+            .filter_map(|cpu| self.values.get(cpu).map(|value| (cpu, value)));
+        Ok(iter)
+    }
+
+    /// Retrieve the values as a slice, indexed by CPU ID.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Convert the object into a vector of values, indexed by CPU ID.
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T> PerCpuValues<T>
+where
+    T: Clone,
+{
+    /// Create a `PerCpuValues` object containing `value` for every
+    /// possible CPU.
+    pub fn from_elem(value: T) -> Result<Self> {
+        let num_cpus = util::num_possible_cpus()?;
+        Ok(Self {
+            values: vec![value; num_cpus],
+        })
+    }
+}
+
+impl<T> PerCpuValues<T>
+where
+    T: NoPadding,
+{
+    /// The distance between two consecutive values in the kernel's
+    /// per-cpu layout.
+    const STRIDE: usize = (size_of::<T>() + 7) & !7;
+
+    /// Decode per-cpu values from the kernel's contiguous per-cpu
+    /// layout, as used by [`MapCore::lookup_percpu_into`] and
+    /// [`MapCore::lookup_percpu_batch`].
+    pub fn from_raw(raw: &[u8]) -> Result<Self> {
+        let num_cpus = util::num_possible_cpus()?;
+        if raw.len() != num_cpus * Self::STRIDE {
+            return Err(Error::with_invalid_data(format!(
+                "per-cpu buffer size {} != {}",
+                raw.len(),
+                num_cpus * Self::STRIDE
+            )));
+        }
+
+        let values = raw
+            .chunks_exact(Self::STRIDE)
+            .map(util::from_bytes)
+            .collect();
+        Ok(Self { values })
+    }
+
+    /// Encode the values in the kernel's contiguous per-cpu layout.
+    pub fn to_raw(&self) -> Vec<u8> {
+        let mut raw = vec![0; self.values.len() * Self::STRIDE];
+        for (chunk, value) in raw.chunks_exact_mut(Self::STRIDE).zip(&self.values) {
+            let () = chunk[..size_of::<T>()].copy_from_slice(util::as_bytes(value));
+        }
+        raw
+    }
+}
+
+impl<T> PerCpuValues<T>
+where
+    T: Copy + Sum<T>,
+{
+    /// Sum up the values of all possible CPUs.
+    pub fn sum(&self) -> T {
+        self.values.iter().copied().sum()
+    }
+}
+
+impl<T> PerCpuValues<T>
+where
+    T: Copy + Ord,
+{
+    /// Retrieve the smallest value of all possible CPUs.
+    pub fn min(&self) -> T {
+        // `PerCpuValues` always contains at least one value.
+        self.values.iter().copied().min().unwrap()
+    }
+
+    /// Retrieve the largest value of all possible CPUs.
+    pub fn max(&self) -> T {
+        // `PerCpuValues` always contains at least one value.
+        self.values.iter().copied().max().unwrap()
+    }
+}
+
+impl<T> Index<usize> for PerCpuValues<T> {
+    type Output = T;
+
+    fn index(&self, cpu: usize) -> &Self::Output {
+        &self.values[cpu]
+    }
+}
+
+impl<T> IndexMut<usize> for PerCpuValues<T> {
+    fn index_mut(&mut self, cpu: usize) -> &mut Self::Output {
+        &mut self.values[cpu]
+    }
+}
+
+impl<'values, T> IntoIterator for &'values PerCpuValues<T> {
+    type Item = &'values T;
+    type IntoIter = slice::Iter<'values, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A typed view on a per-cpu [`MapCore`] object.
+///
+/// Supported are maps of type [`MapType::PercpuHash`],
+/// [`MapType::PercpuArray`], and [`MapType::LruPercpuHash`]. `V` is the
+/// type of the value stored for a single CPU.
+pub struct PerCpuMap<'map, K, V> {
+    map: &'map dyn MapCore,
+    _phantom: PhantomData<fn(K) -> V>,
+}
+
+impl<'map, K, V> PerCpuMap<'map, K, V>
+where
+    K: NoPadding,
+    V: NoPadding,
+{
+    /// Create a new `PerCpuMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of a supported type.
+    /// * If the map's key or value size does not match the size of `K`
+    ///   or `V`, respectively.
+    /// * If the map has BTF type information associated with it and
+    ///   the key or value types' sizes do not match those of `K` or
+    ///   `V`, respectively.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let map_type = map.map_type();
+        if !matches!(
+            map_type,
+            MapType::PercpuHash | MapType::PercpuArray | MapType::LruPercpuHash
+        ) {
+            return Err(Error::with_invalid_data(format!(
+                "map type {map_type:?} is not supported by PerCpuMap"
+            )));
+        }
+
+        let () = check_map_layout::<K, V>(map)?;
+
+        Ok(Self {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Look up the per-cpu values associated with `key`.
+    pub fn get(&self, key: &K) -> Result<Option<PerCpuValues<V>>> {
+        let mut raw = vec![0; util::num_possible_cpus()? * PerCpuValues::<V>::STRIDE];
+        if self
+            .map
+            .lookup_percpu_into(util::as_bytes(key), &mut raw, MapFlags::ANY)?
+        {
+            PerCpuValues::from_raw(&raw).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Insert or update the per-cpu values associated with `key`.
+    pub fn insert(&self, key: &K, values: &PerCpuValues<V>, flags: MapFlags) -> Result<()> {
+        let values = values
+            .iter()
+            .map(|value| util::as_bytes(value).to_vec())
+            .collect::<Vec<_>>();
+        self.map.update_percpu(util::as_bytes(key), &values, flags)
+    }
+
+    /// Remove the values associated with `key`.
+    pub fn remove(&self, key: &K) -> Result<()> {
+        self.map.delete(util::as_bytes(key))
+    }
+
+    /// Look up elements in batch mode, returning an iterator over keys
+    /// and their per-cpu values.
+    ///
+    /// `count` specifies the batch size.
+    pub fn lookup_batch(
+        &self,
+        count: u32,
+        elem_flags: MapFlags,
+        flags: MapFlags,
+    ) -> Result<PerCpuBatchIter<'map, K, V>> {
+        let iter = self.map.lookup_percpu_batch(count, elem_flags, flags)?;
+        Ok(PerCpuBatchIter {
+            iter,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Look up and delete elements in batch mode, returning an iterator
+    /// over the removed keys and their per-cpu values.
+    ///
+    /// `count` specifies the batch size.
+    pub fn lookup_and_delete_batch(
+        &self,
+        count: u32,
+        elem_flags: MapFlags,
+        flags: MapFlags,
+    ) -> Result<PerCpuBatchIter<'map, K, V>> {
+        let iter = self
+            .map
+            .lookup_and_delete_percpu_batch(count, elem_flags, flags)?;
+        Ok(PerCpuBatchIter {
+            iter,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<K, V> Debug for PerCpuMap<'_, K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map, _phantom } = self;
+        f.debug_struct("PerCpuMap").field("map", map).finish()
+    }
+}
+
+/// An iterator over batches of keys and per-cpu values of a
+/// [`PerCpuMap`].
+///
+/// Values that cannot be decoded are reported as errors.
+pub struct PerCpuBatchIter<'map, K, V> {
+    iter: BatchedMapIter<'map>,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> Iterator for PerCpuBatchIter<'_, K, V>
+where
+    K: NoPadding,
+    V: NoPadding,
+{
+    type Item = Result<(K, PerCpuValues<V>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, values) = self.iter.next()?;
+        let result = PerCpuValues::from_raw(&values).map(|values| (util::from_bytes(&key), values));
+        Some(result)
+    }
+}
+
+impl<K, V> Debug for PerCpuBatchIter<'_, K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { iter, _phantom } = self;
+        f.debug_struct("PerCpuBatchIter")
+            .field("iter", iter)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that we can round trip per-cpu values through the
+    /// kernel's per-cpu layout.
+    #[test]
+    fn raw_roundtrip() {
+        let num_cpus = util::num_possible_cpus().unwrap();
+        let values = PerCpuValues::from_vec((0..num_cpus as u32).collect()).unwrap();
+        let raw = values.to_raw();
+        assert_eq!(raw.len(), num_cpus * 8);
+        assert_eq!(PerCpuValues::<u32>::from_raw(&raw).unwrap(), values);
+
+        assert_eq!(values.sum(), (0..num_cpus as u32).sum());
+        assert_eq!(values.min(), 0);
+        assert_eq!(values.max(), num_cpus as u32 - 1);
+        assert_eq!(values[0], 0);
+    }
+}
@@ -1,13 +1,22 @@
 use core::ffi::c_void;
+use std::cell::Cell;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::mem::size_of;
 use std::os::unix::prelude::AsRawFd;
+#[cfg(feature = "tokio")]
+use std::os::unix::prelude::RawFd;
 use std::ptr;
 use std::ptr::NonNull;
 use std::slice;
 use std::time::Duration;
 
+#[cfg(feature = "tokio")]
+use tokio::io::unix::AsyncFd;
+#[cfg(feature = "tokio")]
+use tokio::io::Interest;
+
 use crate::util;
 use crate::util::validate_bpf_ret;
 use crate::AsRawLibbpf;
@@ -19,22 +28,165 @@ use crate::Result;
 
 type SampleCb<'b> = Box<dyn FnMut(i32, &[u8]) + 'b>;
 type LostCb<'b> = Box<dyn FnMut(i32, u64) + 'b>;
+type EventCb<'b> = Box<dyn FnMut(i32, PerfRecord<'_>) + 'b>;
 
 struct CbStruct<'b> {
     sample_cb: Option<SampleCb<'b>>,
     lost_cb: Option<LostCb<'b>>,
+    event_cb: Option<EventCb<'b>>,
+    /// Per-CPU counts of received samples, indexed by CPU.
+    received: Box<[Cell<u64>]>,
+    /// Per-CPU counts of lost samples, indexed by CPU.
+    lost: Box<[Cell<u64>]>,
+}
+
+impl CbStruct<'_> {
+    fn add(counts: &[Cell<u64>], cpu: i32, count: u64) {
+        if let Some(cell) = counts.get(cpu as usize) {
+            let () = cell.set(cell.get().wrapping_add(count));
+        }
+    }
 }
 
 impl Debug for CbStruct<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        let Self { sample_cb, lost_cb } = self;
+        let Self {
+            sample_cb,
+            lost_cb,
+            event_cb,
+            received,
+            lost,
+        } = self;
         f.debug_struct("CbStruct")
             .field("sample_cb", &sample_cb.as_ref().map(|cb| &cb as *const _))
             .field("lost_cb", &lost_cb.as_ref().map(|cb| &cb as *const _))
+            .field("event_cb", &event_cb.as_ref().map(|cb| &cb as *const _))
+            .field("received", received)
+            .field("lost", lost)
             .finish()
     }
 }
 
+/// The kind of a [`PerfRecord`], along with its decoded contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PerfRecordKind<'a> {
+    /// A `PERF_RECORD_SAMPLE` record. The contained data is the record's
+    /// body, the layout of which depends on the `sample_type` of the
+    /// perf event.
+    Sample(&'a [u8]),
+    /// A `PERF_RECORD_LOST` record, reporting samples dropped by the
+    /// kernel because the buffer was full.
+    Lost {
+        /// The ID of the event that lost samples.
+        id: u64,
+        /// The number of lost samples.
+        lost: u64,
+    },
+    /// A `PERF_RECORD_THROTTLE` record.
+    Throttle {
+        /// The time at which throttling started.
+        time: u64,
+        /// The ID of the throttled event.
+        id: u64,
+        /// The stream ID of the throttled event.
+        stream_id: u64,
+    },
+    /// A `PERF_RECORD_UNTHROTTLE` record.
+    Unthrottle {
+        /// The time at which throttling ended.
+        time: u64,
+        /// The ID of the unthrottled event.
+        id: u64,
+        /// The stream ID of the unthrottled event.
+        stream_id: u64,
+    },
+    /// A record of another type.
+    Other(u32),
+}
+
+/// A record read from a [`PerfBuffer`] in raw mode.
+///
+/// A record consists of a `struct perf_event_header` followed by a
+/// type specific body.
+#[derive(Clone, Copy, Debug)]
+pub struct PerfRecord<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PerfRecord<'a> {
+    const HEADER_SIZE: usize = size_of::<libbpf_sys::perf_event_header>();
+
+    fn read_u64(&self, offset: usize) -> Option<u64> {
+        let bytes = self.body().get(offset..offset + size_of::<u64>())?;
+        Some(u64::from_ne_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Retrieve the record's type, i.e., one of the `PERF_RECORD_*`
+    /// constants.
+    pub fn record_type(&self) -> u32 {
+        u32::from_ne_bytes(self.data[0..4].try_into().unwrap())
+    }
+
+    /// Retrieve the record's `misc` header field.
+    pub fn misc(&self) -> u16 {
+        u16::from_ne_bytes(self.data[4..6].try_into().unwrap())
+    }
+
+    /// Retrieve the full record, including its header.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Retrieve the record's body, i.e., everything following the
+    /// header.
+    #[inline]
+    pub fn body(&self) -> &'a [u8] {
+        &self.data[Self::HEADER_SIZE..]
+    }
+
+    /// Decode the record.
+    pub fn kind(&self) -> PerfRecordKind<'a> {
+        let record_type = self.record_type();
+        let throttle = || Some((self.read_u64(0)?, self.read_u64(8)?, self.read_u64(16)?));
+
+        match record_type {
+            libbpf_sys::PERF_RECORD_SAMPLE => PerfRecordKind::Sample(self.body()),
+            libbpf_sys::PERF_RECORD_LOST => match (self.read_u64(0), self.read_u64(8)) {
+                (Some(id), Some(lost)) => PerfRecordKind::Lost { id, lost },
+                _ => PerfRecordKind::Other(record_type),
+            },
+            libbpf_sys::PERF_RECORD_THROTTLE => match throttle() {
+                Some((time, id, stream_id)) => PerfRecordKind::Throttle {
+                    time,
+                    id,
+                    stream_id,
+                },
+                None => PerfRecordKind::Other(record_type),
+            },
+            libbpf_sys::PERF_RECORD_UNTHROTTLE => match throttle() {
+                Some((time, id, stream_id)) => PerfRecordKind::Unthrottle {
+                    time,
+                    id,
+                    stream_id,
+                },
+                None => PerfRecordKind::Other(record_type),
+            },
+            _ => PerfRecordKind::Other(record_type),
+        }
+    }
+}
+
+/// Counters of samples received and lost by a [`PerfBuffer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PerfBufferStats {
+    /// The number of samples received.
+    pub received: u64,
+    /// The number of samples the kernel reported as lost.
+    pub lost: u64,
+}
+
 /// Builds [`PerfBuffer`] instances.
 pub struct PerfBufferBuilder<'a, 'b, M>
 where
@@ -44,6 +196,9 @@ where
     pages: usize,
     sample_cb: Option<SampleCb<'b>>,
     lost_cb: Option<LostCb<'b>>,
+    event_cb: Option<EventCb<'b>>,
+    attr: Option<libbpf_sys::perf_event_attr>,
+    cpus: Vec<(i32, i32)>,
 }
 
 impl<'a, M> PerfBufferBuilder<'a, '_, M>
@@ -58,6 +213,9 @@ where
             pages: 64,
             sample_cb: None,
             lost_cb: None,
+            event_cb: None,
+            attr: None,
+            cpus: Vec::new(),
         }
     }
 }
@@ -77,10 +235,8 @@ where
         F: FnMut(i32, &[u8]) + 'b,
     {
         PerfBufferBuilder {
-            map: self.map,
-            pages: self.pages,
             sample_cb: Some(Box::new(cb)),
-            lost_cb: self.lost_cb,
+            ..self
         }
     }
 
@@ -92,23 +248,59 @@ where
         F: FnMut(i32, u64) + 'b,
     {
         PerfBufferBuilder {
-            map: self.map,
-            pages: self.pages,
-            sample_cb: self.sample_cb,
             lost_cb: Some(Box::new(cb)),
+            ..self
         }
     }
 
-    /// The number of pages to size the ring buffer.
-    pub fn pages(self, pages: usize) -> PerfBufferBuilder<'a, 'b, M> {
+    /// Callback to run for every record read from the buffer, switching
+    /// the `PerfBuffer` into raw mode.
+    ///
+    /// In raw mode, the callback receives full records, including
+    /// `PERF_RECORD_LOST` and throttling records, instead of only
+    /// sample data. Raw mode cannot be combined with
+    /// [`sample_cb`][Self::sample_cb] or [`lost_cb`][Self::lost_cb].
+    ///
+    /// Callback arguments are: `(cpu, record)`.
+    pub fn event_cb<F>(self, cb: F) -> PerfBufferBuilder<'a, 'b, M>
+    where
+        F: FnMut(i32, PerfRecord<'_>) + 'b,
+    {
         PerfBufferBuilder {
-            map: self.map,
-            pages,
-            sample_cb: self.sample_cb,
-            lost_cb: self.lost_cb,
+            event_cb: Some(Box::new(cb)),
+            ..self
+        }
+    }
+
+    /// The `perf_event_attr` to open the per-CPU perf events with in raw
+    /// mode.
+    ///
+    /// By default, `PERF_COUNT_SW_BPF_OUTPUT` software events sampling
+    /// raw data are used.
+    pub fn attr(self, attr: libbpf_sys::perf_event_attr) -> PerfBufferBuilder<'a, 'b, M> {
+        PerfBufferBuilder {
+            attr: Some(attr),
+            ..self
         }
     }
 
+    /// The CPUs to open perf events on in raw mode, each paired with the
+    /// key of the map slot to store the event in.
+    ///
+    /// By default, events are opened on all online CPUs, using the CPU
+    /// number as map key.
+    pub fn cpus(self, cpus: &[(i32, i32)]) -> PerfBufferBuilder<'a, 'b, M> {
+        PerfBufferBuilder {
+            cpus: cpus.to_vec(),
+            ..self
+        }
+    }
+
+    /// The number of pages to size the ring buffer.
+    pub fn pages(self, pages: usize) -> PerfBufferBuilder<'a, 'b, M> {
+        PerfBufferBuilder { pages, ..self }
+    }
+
     /// Build the `PerfBuffer` object as configured.
     pub fn build(self) -> Result<PerfBuffer<'b>> {
         if self.map.map_type() != MapType::PerfEventArray {
@@ -119,43 +311,102 @@ where
             return Err(Error::with_invalid_data("Page count must be power of two"));
         }
 
-        let c_sample_cb: libbpf_sys::perf_buffer_sample_fn = if self.sample_cb.is_some() {
-            Some(Self::call_sample_cb)
-        } else {
-            None
-        };
+        let raw = self.event_cb.is_some();
+        if raw && (self.sample_cb.is_some() || self.lost_cb.is_some()) {
+            return Err(Error::with_invalid_data(
+                "Raw mode cannot be combined with sample or lost callbacks",
+            ));
+        }
 
-        let c_lost_cb: libbpf_sys::perf_buffer_lost_fn = if self.lost_cb.is_some() {
-            Some(Self::call_lost_cb)
-        } else {
-            None
-        };
+        if !raw && (self.attr.is_some() || !self.cpus.is_empty()) {
+            return Err(Error::with_invalid_data(
+                "Custom perf event attributes and CPUs require raw mode",
+            ));
+        }
 
+        let num_cpus = util::num_possible_cpus()?;
         let callback_struct_ptr = Box::into_raw(Box::new(CbStruct {
             sample_cb: self.sample_cb,
             lost_cb: self.lost_cb,
+            event_cb: self.event_cb,
+            received: (0..num_cpus).map(|_| Cell::new(0)).collect(),
+            lost: (0..num_cpus).map(|_| Cell::new(0)).collect(),
         }));
 
-        let ptr = unsafe {
-            libbpf_sys::perf_buffer__new(
-                self.map.as_fd().as_raw_fd(),
-                self.pages as libbpf_sys::size_t,
-                c_sample_cb,
-                c_lost_cb,
-                callback_struct_ptr as *mut _,
-                ptr::null(),
-            )
+        let ptr = if raw {
+            let mut attr = self.attr.unwrap_or_else(|| {
+                let mut attr = libbpf_sys::perf_event_attr {
+                    type_: libbpf_sys::PERF_TYPE_SOFTWARE,
+                    config: libbpf_sys::PERF_COUNT_SW_BPF_OUTPUT as _,
+                    sample_type: libbpf_sys::PERF_SAMPLE_RAW as _,
+                    ..Default::default()
+                };
+                attr.__bindgen_anon_1.sample_period = 1;
+                attr.__bindgen_anon_2.wakeup_events = 1;
+                attr
+            });
+            attr.size = size_of::<libbpf_sys::perf_event_attr>() as _;
+
+            let (mut cpus, mut map_keys): (Vec<_>, Vec<_>) = self.cpus.iter().copied().unzip();
+            let opts = libbpf_sys::perf_buffer_raw_opts {
+                sz: size_of::<libbpf_sys::perf_buffer_raw_opts>() as _,
+                cpu_cnt: cpus.len() as _,
+                cpus: if cpus.is_empty() {
+                    ptr::null_mut()
+                } else {
+                    cpus.as_mut_ptr()
+                },
+                map_keys: if map_keys.is_empty() {
+                    ptr::null_mut()
+                } else {
+                    map_keys.as_mut_ptr()
+                },
+                ..Default::default()
+            };
+
+            unsafe {
+                libbpf_sys::perf_buffer__new_raw(
+                    self.map.as_fd().as_raw_fd(),
+                    self.pages as libbpf_sys::size_t,
+                    &mut attr,
+                    Some(Self::call_event_cb),
+                    callback_struct_ptr as *mut _,
+                    &opts,
+                )
+            }
+        } else {
+            unsafe {
+                libbpf_sys::perf_buffer__new(
+                    self.map.as_fd().as_raw_fd(),
+                    self.pages as libbpf_sys::size_t,
+                    Some(Self::call_sample_cb),
+                    Some(Self::call_lost_cb),
+                    callback_struct_ptr as *mut _,
+                    ptr::null(),
+                )
+            }
         };
+        // Take back ownership before checking for errors, so that the
+        // callbacks get freed in either case.
+        let cb_struct = unsafe { Box::from_raw(callback_struct_ptr) };
         let ptr = validate_bpf_ret(ptr).context("failed to create perf buffer")?;
-        let pb = PerfBuffer {
-            ptr,
-            _cb_struct: unsafe { Box::from_raw(callback_struct_ptr) },
-        };
+        let pb = PerfBuffer { ptr, cb_struct };
         Ok(pb)
     }
 
+    /// Build a new [`AsyncPerfBuffer`].
+    ///
+    /// This method has to be called from within the context of a
+    /// `tokio` runtime with IO enabled.
+    #[cfg(feature = "tokio")]
+    pub fn build_async(self) -> Result<AsyncPerfBuffer<'b>> {
+        let pb = self.build()?;
+        AsyncPerfBuffer::new(pb)
+    }
+
     unsafe extern "C" fn call_sample_cb(ctx: *mut c_void, cpu: i32, data: *mut c_void, size: u32) {
         let callback_struct = ctx as *mut CbStruct<'_>;
+        let () = CbStruct::add(unsafe { &(*callback_struct).received }, cpu, 1);
 
         if let Some(cb) = unsafe { &mut (*callback_struct).sample_cb } {
             let slice = unsafe { slice::from_raw_parts(data as *const u8, size as usize) };
@@ -165,11 +416,41 @@ where
 
     unsafe extern "C" fn call_lost_cb(ctx: *mut c_void, cpu: i32, count: u64) {
         let callback_struct = ctx as *mut CbStruct<'_>;
+        let () = CbStruct::add(unsafe { &(*callback_struct).lost }, cpu, count);
 
         if let Some(cb) = unsafe { &mut (*callback_struct).lost_cb } {
             cb(cpu, count);
         }
     }
+
+    unsafe extern "C" fn call_event_cb(
+        ctx: *mut c_void,
+        cpu: i32,
+        event: *mut libbpf_sys::perf_event_header,
+    ) -> libbpf_sys::bpf_perf_event_ret {
+        let callback_struct = ctx as *mut CbStruct<'_>;
+        // SAFETY: libbpf hands out complete records, copying records
+        //         wrapping around the end of the buffer into a
+        //         contiguous buffer.
+        let size = unsafe { (*event).size } as usize;
+        let data = unsafe { slice::from_raw_parts(event as *const u8, size) };
+        let record = PerfRecord { data };
+
+        match record.kind() {
+            PerfRecordKind::Sample(..) => {
+                let () = CbStruct::add(unsafe { &(*callback_struct).received }, cpu, 1);
+            }
+            PerfRecordKind::Lost { lost, .. } => {
+                let () = CbStruct::add(unsafe { &(*callback_struct).lost }, cpu, lost);
+            }
+            _ => (),
+        }
+
+        if let Some(cb) = unsafe { &mut (*callback_struct).event_cb } {
+            cb(cpu, record);
+        }
+        libbpf_sys::LIBBPF_PERF_EVENT_CONT
+    }
 }
 
 impl<M> Debug for PerfBufferBuilder<'_, '_, M>
@@ -182,12 +463,18 @@ where
             pages,
             sample_cb,
             lost_cb,
+            event_cb,
+            attr,
+            cpus,
         } = self;
         f.debug_struct("PerfBufferBuilder")
             .field("map", map)
             .field("pages", pages)
             .field("sample_cb", &sample_cb.as_ref().map(|cb| &cb as *const _))
             .field("lost_cb", &lost_cb.as_ref().map(|cb| &cb as *const _))
+            .field("event_cb", &event_cb.as_ref().map(|cb| &cb as *const _))
+            .field("attr", &attr.as_ref().map(|attr| attr as *const _))
+            .field("cpus", cpus)
             .finish()
     }
 }
@@ -198,7 +485,7 @@ where
 pub struct PerfBuffer<'b> {
     ptr: NonNull<libbpf_sys::perf_buffer>,
     // Hold onto the box so it'll get dropped when PerfBuffer is dropped
-    _cb_struct: Box<CbStruct<'b>>,
+    cb_struct: Box<CbStruct<'b>>,
 }
 
 // TODO: Document methods.
@@ -239,6 +526,28 @@ impl PerfBuffer<'_> {
         };
         util::parse_ret_i32(ret)
     }
+
+    /// Retrieve the counts of samples received and lost on `cpu` so
+    /// far, or `None` if `cpu` does not exist.
+    ///
+    /// Only samples processed by one of the `poll` or `consume` methods
+    /// are accounted for.
+    pub fn stats(&self, cpu: usize) -> Option<PerfBufferStats> {
+        let received = self.cb_struct.received.get(cpu)?.get();
+        let lost = self.cb_struct.lost.get(cpu)?.get();
+        Some(PerfBufferStats { received, lost })
+    }
+
+    /// Retrieve the counts of samples received and lost across all CPUs
+    /// so far.
+    pub fn total_stats(&self) -> PerfBufferStats {
+        (0..self.cb_struct.received.len())
+            .filter_map(|cpu| self.stats(cpu))
+            .fold(PerfBufferStats::default(), |total, stats| PerfBufferStats {
+                received: total.received.wrapping_add(stats.received),
+                lost: total.lost.wrapping_add(stats.lost),
+            })
+    }
 }
 
 impl AsRawLibbpf for PerfBuffer<'_> {
@@ -261,6 +570,60 @@ impl Drop for PerfBuffer<'_> {
     }
 }
 
+/// A [`PerfBuffer`] that can be consumed asynchronously.
+///
+/// An `AsyncPerfBuffer` registers the perf buffer's epoll file
+/// descriptor with the `tokio` reactor, so that tasks can wait for
+/// data without blocking a thread. It is available with the `tokio`
+/// feature.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncPerfBuffer<'b> {
+    // NB: Has to be dropped before `pb`, so that the epoll file
+    //     descriptor is deregistered before it gets closed.
+    fd: AsyncFd<RawFd>,
+    pb: PerfBuffer<'b>,
+}
+
+#[cfg(feature = "tokio")]
+impl<'b> AsyncPerfBuffer<'b> {
+    /// Create a new `AsyncPerfBuffer` from a [`PerfBuffer`].
+    ///
+    /// This function has to be called from within the context of a
+    /// `tokio` runtime with IO enabled.
+    pub fn new(pb: PerfBuffer<'b>) -> Result<Self> {
+        let fd = AsyncFd::with_interest(pb.epoll_fd(), Interest::READABLE)
+            .context("failed to register perf buffer with tokio reactor")?;
+        Ok(Self { fd, pb })
+    }
+
+    /// Wait until data is available in any of the per-cpu buffers and
+    /// consume it, calling the registered callbacks.
+    ///
+    /// This method is cancellation safe: if the returned future is
+    /// dropped before completion, no data will have been consumed.
+    pub async fn consume(&mut self) -> Result<()> {
+        let mut guard = self.fd.readable().await?;
+        let () = self.pb.consume()?;
+        let () = guard.clear_ready();
+        Ok(())
+    }
+
+    /// Retrieve a reference to the underlying [`PerfBuffer`].
+    #[inline]
+    pub fn get_ref(&self) -> &PerfBuffer<'b> {
+        &self.pb
+    }
+
+    /// Deregister the perf buffer from the `tokio` reactor and
+    /// retrieve the underlying [`PerfBuffer`].
+    pub fn into_inner(self) -> PerfBuffer<'b> {
+        let Self { fd, pb } = self;
+        let _fd = fd.into_inner();
+        pb
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
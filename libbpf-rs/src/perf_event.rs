@@ -0,0 +1,305 @@
+use std::fs;
+use std::io;
+use std::mem::size_of;
+use std::os::unix::io::FromRawFd as _;
+use std::os::unix::io::OwnedFd;
+use std::path::Path;
+
+use crate::util;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Result;
+
+// from kernel @ include/uapi/linux/perf_event.h
+const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+/// A generalized hardware event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HardwareEvent {
+    /// Total CPU cycles.
+    CpuCycles,
+    /// Retired instructions.
+    Instructions,
+    /// Cache accesses.
+    CacheReferences,
+    /// Cache misses.
+    CacheMisses,
+    /// Retired branch instructions.
+    BranchInstructions,
+    /// Mispredicted branch instructions.
+    BranchMisses,
+    /// Bus cycles.
+    BusCycles,
+    /// Stalled cycles during issue.
+    StalledCyclesFrontend,
+    /// Stalled cycles during retirement.
+    StalledCyclesBackend,
+    /// Total CPU cycles, not affected by CPU frequency scaling.
+    RefCpuCycles,
+}
+
+impl From<HardwareEvent> for u64 {
+    fn from(event: HardwareEvent) -> Self {
+        let config = match event {
+            HardwareEvent::CpuCycles => libbpf_sys::PERF_COUNT_HW_CPU_CYCLES,
+            HardwareEvent::Instructions => libbpf_sys::PERF_COUNT_HW_INSTRUCTIONS,
+            HardwareEvent::CacheReferences => libbpf_sys::PERF_COUNT_HW_CACHE_REFERENCES,
+            HardwareEvent::CacheMisses => libbpf_sys::PERF_COUNT_HW_CACHE_MISSES,
+            HardwareEvent::BranchInstructions => libbpf_sys::PERF_COUNT_HW_BRANCH_INSTRUCTIONS,
+            HardwareEvent::BranchMisses => libbpf_sys::PERF_COUNT_HW_BRANCH_MISSES,
+            HardwareEvent::BusCycles => libbpf_sys::PERF_COUNT_HW_BUS_CYCLES,
+            HardwareEvent::StalledCyclesFrontend => {
+                libbpf_sys::PERF_COUNT_HW_STALLED_CYCLES_FRONTEND
+            }
+            HardwareEvent::StalledCyclesBackend => libbpf_sys::PERF_COUNT_HW_STALLED_CYCLES_BACKEND,
+            HardwareEvent::RefCpuCycles => libbpf_sys::PERF_COUNT_HW_REF_CPU_CYCLES,
+        };
+        config as u64
+    }
+}
+
+/// A software event provided by the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SoftwareEvent {
+    /// A high-resolution per-CPU timer.
+    CpuClock,
+    /// A clock count specific to the task that is running.
+    TaskClock,
+    /// Page faults.
+    PageFaults,
+    /// Context switches.
+    ContextSwitches,
+    /// Migrations of a process to a new CPU.
+    CpuMigrations,
+    /// Minor page faults.
+    PageFaultsMin,
+    /// Major page faults.
+    PageFaultsMaj,
+    /// Alignment faults.
+    AlignmentFaults,
+    /// Emulation faults.
+    EmulationFaults,
+    /// A placeholder event that counts nothing.
+    Dummy,
+    /// An event used for emitting data from BPF programs.
+    BpfOutput,
+}
+
+impl From<SoftwareEvent> for u64 {
+    fn from(event: SoftwareEvent) -> Self {
+        let config = match event {
+            SoftwareEvent::CpuClock => libbpf_sys::PERF_COUNT_SW_CPU_CLOCK,
+            SoftwareEvent::TaskClock => libbpf_sys::PERF_COUNT_SW_TASK_CLOCK,
+            SoftwareEvent::PageFaults => libbpf_sys::PERF_COUNT_SW_PAGE_FAULTS,
+            SoftwareEvent::ContextSwitches => libbpf_sys::PERF_COUNT_SW_CONTEXT_SWITCHES,
+            SoftwareEvent::CpuMigrations => libbpf_sys::PERF_COUNT_SW_CPU_MIGRATIONS,
+            SoftwareEvent::PageFaultsMin => libbpf_sys::PERF_COUNT_SW_PAGE_FAULTS_MIN,
+            SoftwareEvent::PageFaultsMaj => libbpf_sys::PERF_COUNT_SW_PAGE_FAULTS_MAJ,
+            SoftwareEvent::AlignmentFaults => libbpf_sys::PERF_COUNT_SW_ALIGNMENT_FAULTS,
+            SoftwareEvent::EmulationFaults => libbpf_sys::PERF_COUNT_SW_EMULATION_FAULTS,
+            SoftwareEvent::Dummy => libbpf_sys::PERF_COUNT_SW_DUMMY,
+            SoftwareEvent::BpfOutput => libbpf_sys::PERF_COUNT_SW_BPF_OUTPUT,
+        };
+        config as u64
+    }
+}
+
+/// A builder for perf events, as created by
+/// [`perf_event_open(2)`](https://man7.org/linux/man-pages/man2/perf_event_open.2.html).
+///
+/// Perf events can be used to trigger BPF programs of type
+/// [`ProgramType::PerfEvent`][crate::ProgramType::PerfEvent], which
+/// get attached using
+/// [`ProgramMut::attach_perf_event_with_opts`][crate::ProgramMut::attach_perf_event_with_opts].
+///
+/// An event is scoped to a process, to a CPU, or to a process on a
+/// specific CPU. For example, to sample all CPUs at 99 Hz:
+/// ```no_run
+/// # use libbpf_rs::PerfEvent;
+/// # use libbpf_rs::SoftwareEvent;
+/// let fds = PerfEvent::software(SoftwareEvent::CpuClock)
+///     .sample_freq(99)
+///     .open_per_cpu()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PerfEvent {
+    type_: u32,
+    config: u64,
+    sample: Option<(u64, bool)>,
+    inherit: bool,
+    exclude_kernel: bool,
+    exclude_user: bool,
+    pid: i32,
+    cpu: i32,
+}
+
+impl PerfEvent {
+    /// Create a perf event of the given type and type specific
+    /// configuration.
+    ///
+    /// This constructor can be used for raw hardware events
+    /// (`PERF_TYPE_RAW`) and for events of dynamic PMUs, the type of
+    /// which is advertised in `/sys/bus/event_source/devices/*/type`.
+    pub fn raw(type_: u32, config: u64) -> Self {
+        Self {
+            type_,
+            config,
+            sample: None,
+            inherit: false,
+            exclude_kernel: false,
+            exclude_user: false,
+            pid: -1,
+            cpu: -1,
+        }
+    }
+
+    /// Create a generalized hardware event.
+    pub fn hardware(event: HardwareEvent) -> Self {
+        Self::raw(libbpf_sys::PERF_TYPE_HARDWARE, event.into())
+    }
+
+    /// Create a software event.
+    pub fn software(event: SoftwareEvent) -> Self {
+        Self::raw(libbpf_sys::PERF_TYPE_SOFTWARE, event.into())
+    }
+
+    /// Create an event for the kernel tracepoint with the given ID.
+    pub fn tracepoint(id: u64) -> Self {
+        Self::raw(libbpf_sys::PERF_TYPE_TRACEPOINT, id)
+    }
+
+    /// Create an event for the kernel tracepoint `category:name`,
+    /// looking up its ID in tracefs.
+    pub fn tracepoint_by_name(category: &str, name: &str) -> Result<Self> {
+        let id = ["/sys/kernel/tracing", "/sys/kernel/debug/tracing"]
+            .iter()
+            .map(|tracefs| Path::new(tracefs).join(format!("events/{category}/{name}/id")))
+            .find_map(|path| fs::read_to_string(path).ok())
+            .ok_or_else(|| {
+                Error::with_io_error(
+                    io::ErrorKind::NotFound,
+                    format!("failed to find tracepoint `{category}:{name}`"),
+                )
+            })?;
+        let id = id.trim().parse::<u64>().map_err(|_err| {
+            Error::with_invalid_data(format!("invalid ID of tracepoint `{category}:{name}`"))
+        })?;
+        Ok(Self::tracepoint(id))
+    }
+
+    /// Generate a sample every `period` events.
+    pub fn sample_period(&mut self, period: u64) -> &mut Self {
+        self.sample = Some((period, false));
+        self
+    }
+
+    /// Generate samples at a frequency of `freq` samples per second,
+    /// with the kernel adjusting the sample period dynamically.
+    pub fn sample_freq(&mut self, freq: u64) -> &mut Self {
+        self.sample = Some((freq, true));
+        self
+    }
+
+    /// Whether child tasks created after the event was opened inherit
+    /// the event.
+    pub fn inherit(&mut self, inherit: bool) -> &mut Self {
+        self.inherit = inherit;
+        self
+    }
+
+    /// Whether to exclude events occurring in kernel space.
+    pub fn exclude_kernel(&mut self, exclude: bool) -> &mut Self {
+        self.exclude_kernel = exclude;
+        self
+    }
+
+    /// Whether to exclude events occurring in user space.
+    pub fn exclude_user(&mut self, exclude: bool) -> &mut Self {
+        self.exclude_user = exclude;
+        self
+    }
+
+    /// Restrict the event to the process or thread with the given ID. A
+    /// `pid` of 0 refers to the calling thread.
+    pub fn pid(&mut self, pid: i32) -> &mut Self {
+        self.pid = pid;
+        self
+    }
+
+    /// Restrict the event to the given CPU.
+    pub fn cpu(&mut self, cpu: usize) -> &mut Self {
+        self.cpu = cpu as i32;
+        self
+    }
+
+    fn attr(&self) -> libbpf_sys::perf_event_attr {
+        let mut attr = libbpf_sys::perf_event_attr {
+            type_: self.type_,
+            size: size_of::<libbpf_sys::perf_event_attr>() as _,
+            config: self.config,
+            ..Default::default()
+        };
+
+        if let Some((value, freq)) = self.sample {
+            if freq {
+                attr.__bindgen_anon_1.sample_freq = value;
+            } else {
+                attr.__bindgen_anon_1.sample_period = value;
+            }
+            let () = attr.set_freq(freq.into());
+        }
+        let () = attr.set_inherit(self.inherit.into());
+        let () = attr.set_exclude_kernel(self.exclude_kernel.into());
+        let () = attr.set_exclude_user(self.exclude_user.into());
+        attr
+    }
+
+    fn open_on(&self, cpu: i32) -> Result<OwnedFd> {
+        if self.pid == -1 && cpu == -1 {
+            return Err(Error::with_invalid_data(
+                "perf event has to be restricted to a process or a CPU",
+            ));
+        }
+
+        let attr = self.attr();
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const libbpf_sys::perf_event_attr,
+                self.pid,
+                cpu,
+                -1,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if ret < 0 {
+            return Err(Error::from(io::Error::last_os_error()))
+                .context("failed to open perf event");
+        }
+        // SAFETY: `perf_event_open` returned a valid file descriptor,
+        //         which we now own.
+        Ok(unsafe { OwnedFd::from_raw_fd(ret as _) })
+    }
+
+    /// Open the perf event, as configured.
+    ///
+    /// # Errors
+    /// * If the event is restricted to neither a process nor a CPU.
+    pub fn open(&self) -> Result<OwnedFd> {
+        self.open_on(self.cpu)
+    }
+
+    /// Open the perf event once on each online CPU, ignoring any CPU
+    /// set using [`PerfEvent::cpu`].
+    ///
+    /// The returned file descriptors are ordered by CPU.
+    pub fn open_per_cpu(&self) -> Result<Vec<OwnedFd>> {
+        util::online_cpus()?
+            .into_iter()
+            .map(|cpu| self.open_on(cpu as i32))
+            .collect()
+    }
+}
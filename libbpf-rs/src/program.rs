@@ -16,6 +16,7 @@ use std::os::unix::io::AsFd;
 use std::os::unix::io::AsRawFd;
 use std::os::unix::io::BorrowedFd;
 use std::os::unix::io::FromRawFd;
+use std::os::unix::io::IntoRawFd as _;
 use std::os::unix::io::OwnedFd;
 use std::path::Path;
 use std::ptr;
@@ -33,7 +34,9 @@ use crate::Error;
 use crate::ErrorExt as _;
 use crate::Link;
 use crate::Mut;
+use crate::NetkitOpts;
 use crate::Result;
+use crate::TcxOpts;
 
 /// Options to optionally be provided when attaching to a uprobe.
 #[derive(Clone, Debug, Default)]
@@ -57,6 +60,171 @@ pub struct UprobeOpts {
     pub _non_exhaustive: (),
 }
 
+/// The mode used for attaching a kprobe or uprobe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProbeAttachMode {
+    /// Let libbpf pick the best available mode.
+    #[default]
+    Default,
+    /// Use the legacy, tracefs based, probe creation and attach the
+    /// program through a perf event.
+    Legacy,
+    /// Create the probe using `perf_event_open` and attach the program
+    /// through the perf event, without creating a BPF link.
+    Perf,
+    /// Create the probe using `perf_event_open` and attach the program
+    /// through a BPF perf link.
+    Link,
+}
+
+impl From<ProbeAttachMode> for libbpf_sys::probe_attach_mode {
+    fn from(mode: ProbeAttachMode) -> Self {
+        match mode {
+            ProbeAttachMode::Default => libbpf_sys::PROBE_ATTACH_MODE_DEFAULT,
+            ProbeAttachMode::Legacy => libbpf_sys::PROBE_ATTACH_MODE_LEGACY,
+            ProbeAttachMode::Perf => libbpf_sys::PROBE_ATTACH_MODE_PERF,
+            ProbeAttachMode::Link => libbpf_sys::PROBE_ATTACH_MODE_LINK,
+        }
+    }
+}
+
+/// Options to optionally be provided when attaching to a kprobe.
+#[derive(Clone, Debug, Default)]
+pub struct KprobeOpts {
+    /// Custom user-provided value accessible through `bpf_get_attach_cookie`.
+    pub cookie: u64,
+    /// Offset of the probe within the function.
+    pub offset: usize,
+    /// kprobe is return probe, invoked at function return time.
+    pub retprobe: bool,
+    /// The mode to attach the kprobe in.
+    pub attach_mode: ProbeAttachMode,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl From<KprobeOpts> for libbpf_sys::bpf_kprobe_opts {
+    fn from(opts: KprobeOpts) -> Self {
+        let KprobeOpts {
+            cookie,
+            offset,
+            retprobe,
+            attach_mode,
+            _non_exhaustive,
+        } = opts;
+
+        #[allow(clippy::needless_update)]
+        libbpf_sys::bpf_kprobe_opts {
+            sz: size_of::<Self>() as _,
+            bpf_cookie: cookie,
+            offset: offset as libbpf_sys::size_t,
+            retprobe,
+            attach_mode: attach_mode.into(),
+            // bpf_kprobe_opts might have padding fields on some platform
+            ..Default::default()
+        }
+    }
+}
+
+/// Options to be provided when attaching a program to multiple kernel
+/// functions at once.
+///
+/// Exactly one of `pattern`, `symbols`, and `addresses` has to be set.
+#[derive(Clone, Debug, Default)]
+pub struct KprobeMultiOpts {
+    /// A glob pattern of the function names to attach to, e.g.,
+    /// `tcp_*`.
+    pub pattern: Option<String>,
+    /// The names of the functions to attach to.
+    pub symbols: Vec<String>,
+    /// The addresses of the functions to attach to.
+    pub addresses: Vec<usize>,
+    /// Custom user-provided values accessible through
+    /// `bpf_get_attach_cookie`, one for each entry in `symbols` or
+    /// `addresses`. May be empty.
+    pub cookies: Vec<u64>,
+    /// kprobes are return probes, invoked at function return time.
+    pub retprobe: bool,
+    /// Attach the program as session probe, invoked at function entry
+    /// and return time.
+    pub session: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Options to be provided when attaching a program to multiple user
+/// space functions at once.
+///
+/// Exactly one of `pattern`, `symbols`, and `offsets` has to be set.
+#[derive(Clone, Debug, Default)]
+pub struct UprobeMultiOpts {
+    /// A glob pattern of the function names to attach to, e.g.,
+    /// `malloc*`.
+    pub pattern: Option<String>,
+    /// The names of the functions to attach to.
+    pub symbols: Vec<String>,
+    /// The offsets of the functions to attach to within the binary.
+    pub offsets: Vec<usize>,
+    /// Offsets of kernel reference counted USDT semaphores, one for each
+    /// entry in `symbols` or `offsets`. May be empty.
+    pub ref_ctr_offsets: Vec<usize>,
+    /// Custom user-provided values accessible through
+    /// `bpf_get_attach_cookie`, one for each entry in `symbols` or
+    /// `offsets`. May be empty.
+    pub cookies: Vec<u64>,
+    /// uprobes are return probes, invoked at function return time.
+    pub retprobe: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Determine the number of functions targeted by a multi-probe.
+///
+/// Exactly one of a pattern, a list of symbols, or a list of
+/// addresses/offsets is accepted. Per-function attributes, such as
+/// cookies, have to be empty or match the number of functions.
+fn multi_probe_cnt(
+    pattern: bool,
+    symbols: usize,
+    (addrs_name, addrs): (&str, usize),
+    attrs: &[(&str, usize)],
+) -> Result<usize> {
+    let cnt = match (pattern, symbols, addrs) {
+        (true, 0, 0) => 0,
+        (false, cnt, 0) | (false, 0, cnt) if cnt != 0 => cnt,
+        _ => {
+            return Err(Error::with_invalid_data(format!(
+                "exactly one of pattern, symbols, and {addrs_name} has to be provided"
+            )))
+        }
+    };
+
+    for (name, len) in attrs {
+        if *len != 0 && pattern {
+            return Err(Error::with_invalid_data(format!(
+                "{name} cannot be used with a pattern"
+            )));
+        }
+
+        if *len != 0 && *len != cnt {
+            return Err(Error::with_invalid_data(format!(
+                "number of {name} ({len}) does not match number of functions ({cnt})"
+            )));
+        }
+    }
+    Ok(cnt)
+}
+
+/// Retrieve a pointer to the first element of `slice`, or NULL if it is
+/// empty.
+fn slice_ptr_or_null<T>(slice: &[T]) -> *const T {
+    if slice.is_empty() {
+        ptr::null()
+    } else {
+        slice.as_ptr()
+    }
+}
+
 /// Options to optionally be provided when attaching to a USDT.
 #[derive(Clone, Debug, Default)]
 pub struct UsdtOpts {
@@ -82,6 +250,32 @@ impl From<UsdtOpts> for libbpf_sys::bpf_usdt_opts {
     }
 }
 
+/// Options to optionally be provided when attaching to a perf event.
+#[derive(Clone, Debug, Default)]
+pub struct PerfEventOpts {
+    /// Custom user-provided value accessible through `bpf_get_attach_cookie`.
+    pub cookie: u64,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl From<PerfEventOpts> for libbpf_sys::bpf_perf_event_opts {
+    fn from(opts: PerfEventOpts) -> Self {
+        let PerfEventOpts {
+            cookie,
+            _non_exhaustive,
+        } = opts;
+
+        #[allow(clippy::needless_update)]
+        libbpf_sys::bpf_perf_event_opts {
+            sz: size_of::<Self>() as _,
+            bpf_cookie: cookie,
+            // bpf_perf_event_opts might have padding fields on some platform
+            ..Default::default()
+        }
+    }
+}
+
 /// Options to optionally be provided when attaching to a tracepoint.
 #[derive(Clone, Debug, Default)]
 pub struct TracepointOpts {
@@ -108,6 +302,32 @@ impl From<TracepointOpts> for libbpf_sys::bpf_tracepoint_opts {
     }
 }
 
+/// Options to optionally be provided when attaching to a fentry/fexit
+/// kernel probe or other tracing hook.
+#[derive(Clone, Debug, Default)]
+pub struct TraceOpts {
+    /// Custom user-provided value accessible through `bpf_get_attach_cookie`.
+    pub cookie: u64,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl From<TraceOpts> for libbpf_sys::bpf_trace_opts {
+    fn from(opts: TraceOpts) -> Self {
+        let TraceOpts {
+            cookie,
+            _non_exhaustive,
+        } = opts;
+
+        #[allow(clippy::needless_update)]
+        libbpf_sys::bpf_trace_opts {
+            sz: size_of::<Self>() as _,
+            cookie,
+            // bpf_trace_opts might have padding fields on some platform
+            ..Default::default()
+        }
+    }
+}
 
 /// An immutable parsed but not yet loaded BPF program.
 pub type OpenProgram<'obj> = OpenProgramImpl<'obj>;
@@ -452,6 +672,21 @@ pub enum ProgramAttachType {
     SkReuseportSelect,
     SkReuseportSelectOrMigrate,
     PerfEvent,
+    TraceKprobeMulti,
+    LsmCgroup,
+    StructOps,
+    Netfilter,
+    TcxIngress,
+    TcxEgress,
+    TraceUprobeMulti,
+    CgroupUnixConnect,
+    CgroupUnixSendmsg,
+    CgroupUnixRecvmsg,
+    CgroupUnixGetpeername,
+    CgroupUnixGetsockname,
+    NetkitPrimary,
+    NetkitPeer,
+    TraceKprobeSession,
     /// See [`MapType::Unknown`][crate::MapType::Unknown]
     Unknown = u32::MAX,
 }
@@ -503,6 +738,21 @@ impl From<u32> for ProgramAttachType {
             x if x == SkReuseportSelect as u32 => SkReuseportSelect,
             x if x == SkReuseportSelectOrMigrate as u32 => SkReuseportSelectOrMigrate,
             x if x == PerfEvent as u32 => PerfEvent,
+            x if x == TraceKprobeMulti as u32 => TraceKprobeMulti,
+            x if x == LsmCgroup as u32 => LsmCgroup,
+            x if x == StructOps as u32 => StructOps,
+            x if x == Netfilter as u32 => Netfilter,
+            x if x == TcxIngress as u32 => TcxIngress,
+            x if x == TcxEgress as u32 => TcxEgress,
+            x if x == TraceUprobeMulti as u32 => TraceUprobeMulti,
+            x if x == CgroupUnixConnect as u32 => CgroupUnixConnect,
+            x if x == CgroupUnixSendmsg as u32 => CgroupUnixSendmsg,
+            x if x == CgroupUnixRecvmsg as u32 => CgroupUnixRecvmsg,
+            x if x == CgroupUnixGetpeername as u32 => CgroupUnixGetpeername,
+            x if x == CgroupUnixGetsockname as u32 => CgroupUnixGetsockname,
+            x if x == NetkitPrimary as u32 => NetkitPrimary,
+            x if x == NetkitPeer as u32 => NetkitPeer,
+            x if x == TraceKprobeSession as u32 => TraceKprobeSession,
             _ => Unknown,
         }
     }
@@ -771,6 +1021,35 @@ impl<'obj> ProgramMut<'obj> {
         Ok(link)
     }
 
+    /// Attach this program to a [perf event](https://linux.die.net/man/2/perf_event_open),
+    /// providing additional options.
+    ///
+    /// The perf event, e.g., as opened by [`PerfEvent`][crate::PerfEvent],
+    /// is enabled as part of the attachment. The link keeps a duplicate
+    /// of `pfd` and disables the event once detached.
+    pub fn attach_perf_event_with_opts(&self, pfd: impl AsFd, opts: PerfEventOpts) -> Result<Link> {
+        // libbpf takes ownership of the file descriptor and closes it
+        // when the link is destroyed.
+        let pfd = pfd
+            .as_fd()
+            .try_clone_to_owned()
+            .context("failed to duplicate perf event file descriptor")?;
+        let opts = libbpf_sys::bpf_perf_event_opts::from(opts);
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_perf_event_opts(
+                self.ptr.as_ptr(),
+                pfd.as_raw_fd(),
+                &opts as *const _,
+            )
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach perf event")?;
+        // The link now owns the file descriptor.
+        let _fd = pfd.into_raw_fd();
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
     /// Attach this program to a [userspace
     /// probe](https://www.kernel.org/doc/html/latest/trace/uprobetracer.html).
     pub fn attach_uprobe<T: AsRef<Path>>(
@@ -842,6 +1121,109 @@ impl<'obj> ProgramMut<'obj> {
         Ok(link)
     }
 
+    /// Attach this program to all functions matching the glob
+    /// `func_pattern` in the binary at `binary_path`, using a single
+    /// [uprobe.multi link](https://lwn.net/Articles/941035/).
+    ///
+    /// If `pid` is -1, the probes trigger in all processes, otherwise
+    /// only in the process with the given PID.
+    pub fn attach_uprobe_multi(
+        &self,
+        pid: i32,
+        binary_path: impl AsRef<Path>,
+        func_pattern: impl AsRef<str>,
+        retprobe: bool,
+    ) -> Result<Link> {
+        let opts = UprobeMultiOpts {
+            pattern: Some(func_pattern.as_ref().to_string()),
+            retprobe,
+            ..Default::default()
+        };
+        self.attach_uprobe_multi_with_opts(pid, binary_path, opts)
+    }
+
+    /// Attach this program to multiple functions in the binary at
+    /// `binary_path`, using a single [uprobe.multi
+    /// link](https://lwn.net/Articles/941035/).
+    ///
+    /// If `pid` is -1, the probes trigger in all processes, otherwise
+    /// only in the process with the given PID.
+    pub fn attach_uprobe_multi_with_opts(
+        &self,
+        pid: i32,
+        binary_path: impl AsRef<Path>,
+        opts: UprobeMultiOpts,
+    ) -> Result<Link> {
+        let UprobeMultiOpts {
+            pattern,
+            symbols,
+            offsets,
+            ref_ctr_offsets,
+            cookies,
+            retprobe,
+            _non_exhaustive,
+        } = opts;
+
+        let cnt = multi_probe_cnt(
+            pattern.is_some(),
+            symbols.len(),
+            ("offsets", offsets.len()),
+            &[
+                ("ref_ctr_offsets", ref_ctr_offsets.len()),
+                ("cookies", cookies.len()),
+            ],
+        )?;
+
+        let path = util::path_to_cstring(binary_path)?;
+        let path_ptr = path.as_ptr();
+        let pattern = pattern.as_deref().map(util::str_to_cstring).transpose()?;
+        let pattern_ptr = pattern
+            .as_ref()
+            .map(|pattern| pattern.as_ptr())
+            .unwrap_or_else(ptr::null);
+        let symbols = symbols
+            .iter()
+            .map(|symbol| util::str_to_cstring(symbol))
+            .collect::<Result<Vec<_>>>()?;
+        let symbol_ptrs = symbols
+            .iter()
+            .map(|symbol| symbol.as_ptr())
+            .collect::<Vec<_>>();
+        let offsets = offsets
+            .into_iter()
+            .map(|offset| offset as libc::c_ulong)
+            .collect::<Vec<_>>();
+        let ref_ctr_offsets = ref_ctr_offsets
+            .into_iter()
+            .map(|offset| offset as libc::c_ulong)
+            .collect::<Vec<_>>();
+
+        let opts = libbpf_sys::bpf_uprobe_multi_opts {
+            sz: size_of::<libbpf_sys::bpf_uprobe_multi_opts>() as _,
+            syms: slice_ptr_or_null(&symbol_ptrs).cast_mut(),
+            offsets: slice_ptr_or_null(&offsets),
+            ref_ctr_offsets: slice_ptr_or_null(&ref_ctr_offsets),
+            cookies: slice_ptr_or_null(&cookies),
+            cnt: cnt as libbpf_sys::size_t,
+            retprobe,
+            ..Default::default()
+        };
+
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_uprobe_multi(
+                self.ptr.as_ptr(),
+                pid,
+                path_ptr,
+                pattern_ptr,
+                &opts as *const _,
+            )
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach uprobe.multi")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
     /// Attach this program to a [kernel
     /// probe](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html).
     pub fn attach_kprobe<T: AsRef<str>>(&self, retprobe: bool, func_name: T) -> Result<Link> {
@@ -856,6 +1238,104 @@ impl<'obj> ProgramMut<'obj> {
         Ok(link)
     }
 
+    /// Attach this program to a [kernel
+    /// probe](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html),
+    /// providing additional options.
+    pub fn attach_kprobe_with_opts(
+        &self,
+        func_name: impl AsRef<str>,
+        opts: KprobeOpts,
+    ) -> Result<Link> {
+        let func_name = util::str_to_cstring(func_name.as_ref())?;
+        let func_name_ptr = func_name.as_ptr();
+        let opts = libbpf_sys::bpf_kprobe_opts::from(opts);
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_kprobe_opts(
+                self.ptr.as_ptr(),
+                func_name_ptr,
+                &opts as *const _,
+            )
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach kprobe")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
+    /// Attach this program to all kernel functions matching the glob
+    /// `pattern`, using a single [kprobe.multi
+    /// link](https://lwn.net/Articles/885811/).
+    pub fn attach_kprobe_multi(&self, retprobe: bool, pattern: impl AsRef<str>) -> Result<Link> {
+        let opts = KprobeMultiOpts {
+            pattern: Some(pattern.as_ref().to_string()),
+            retprobe,
+            ..Default::default()
+        };
+        self.attach_kprobe_multi_with_opts(opts)
+    }
+
+    /// Attach this program to multiple kernel functions, using a single
+    /// [kprobe.multi link](https://lwn.net/Articles/885811/).
+    pub fn attach_kprobe_multi_with_opts(&self, opts: KprobeMultiOpts) -> Result<Link> {
+        let KprobeMultiOpts {
+            pattern,
+            symbols,
+            addresses,
+            cookies,
+            retprobe,
+            session,
+            _non_exhaustive,
+        } = opts;
+
+        let cnt = multi_probe_cnt(
+            pattern.is_some(),
+            symbols.len(),
+            ("addresses", addresses.len()),
+            &[("cookies", cookies.len())],
+        )?;
+
+        let pattern = pattern.as_deref().map(util::str_to_cstring).transpose()?;
+        let pattern_ptr = pattern
+            .as_ref()
+            .map(|pattern| pattern.as_ptr())
+            .unwrap_or_else(ptr::null);
+        let symbols = symbols
+            .iter()
+            .map(|symbol| util::str_to_cstring(symbol))
+            .collect::<Result<Vec<_>>>()?;
+        let symbol_ptrs = symbols
+            .iter()
+            .map(|symbol| symbol.as_ptr())
+            .collect::<Vec<_>>();
+        let addresses = addresses
+            .into_iter()
+            .map(|addr| addr as libc::c_ulong)
+            .collect::<Vec<_>>();
+
+        let opts = libbpf_sys::bpf_kprobe_multi_opts {
+            sz: size_of::<libbpf_sys::bpf_kprobe_multi_opts>() as _,
+            syms: slice_ptr_or_null(&symbol_ptrs).cast_mut(),
+            addrs: slice_ptr_or_null(&addresses),
+            cookies: slice_ptr_or_null(&cookies),
+            cnt: cnt as libbpf_sys::size_t,
+            retprobe,
+            session,
+            ..Default::default()
+        };
+
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_kprobe_multi_opts(
+                self.ptr.as_ptr(),
+                pattern_ptr,
+                &opts as *const _,
+            )
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach kprobe.multi")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
     /// Attach this program to the specified syscall
     pub fn attach_ksyscall<T: AsRef<str>>(&self, retprobe: bool, syscall_name: T) -> Result<Link> {
         let opts = libbpf_sys::bpf_ksyscall_opts {
@@ -966,12 +1446,64 @@ impl<'obj> ProgramMut<'obj> {
         Ok(link)
     }
 
+    /// Attach to a [fentry/fexit kernel probe](https://lwn.net/Articles/801479/)
+    /// or other tracing hook, providing additional options.
+    ///
+    /// The attach target is the one the program was loaded for, as
+    /// specified by its section or by
+    /// [`OpenProgramImpl::set_attach_target`].
+    pub fn attach_trace_with_opts(&self, opts: TraceOpts) -> Result<Link> {
+        let opts = libbpf_sys::bpf_trace_opts::from(opts);
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_trace_opts(self.ptr.as_ptr(), &opts as *const _)
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach fentry/fexit kernel probe")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
+    /// Attach this `freplace` program to replace the global function
+    /// `func_name` of the already loaded BPF program `target_prog`.
+    ///
+    /// The replacement stays in effect for as long as the returned link
+    /// exists. The same program can be attached to multiple targets,
+    /// as long as their function signatures match the one the program
+    /// was loaded for. A program only known by ID can be opened using
+    /// [`Program::fd_from_id`].
+    pub fn attach_freplace(
+        &self,
+        target_prog: BorrowedFd<'_>,
+        func_name: impl AsRef<str>,
+    ) -> Result<Link> {
+        let func_name = util::str_to_cstring(func_name.as_ref())?;
+        let func_name_ptr = func_name.as_ptr();
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_freplace(
+                self.ptr.as_ptr(),
+                target_prog.as_raw_fd(),
+                func_name_ptr,
+            )
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach freplace program")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
     /// Attach a verdict/parser to a [sockmap/sockhash](https://lwn.net/Articles/731133/)
-    pub fn attach_sockmap(&self, map_fd: i32) -> Result<()> {
+    ///
+    /// The attach type, e.g., [`ProgramAttachType::SkSkbStreamParser`]
+    /// or [`ProgramAttachType::SkMsgVerdict`], is determined by the
+    /// program's section or as set using
+    /// [`OpenProgramMut::set_attach_type`]. The program stays attached
+    /// until detached using [`ProgramMut::detach_sockmap`] or the map is
+    /// destroyed.
+    pub fn attach_sockmap(&self, map: BorrowedFd<'_>) -> Result<()> {
         let err = unsafe {
             libbpf_sys::bpf_prog_attach(
                 self.as_fd().as_raw_fd(),
-                map_fd,
+                map.as_raw_fd(),
                 self.attach_type() as u32,
                 0,
             )
@@ -979,6 +1511,57 @@ impl<'obj> ProgramMut<'obj> {
         util::parse_ret(err)
     }
 
+    /// Detach a verdict/parser attached using
+    /// [`ProgramMut::attach_sockmap`] from a sockmap/sockhash.
+    pub fn detach_sockmap(&self, map: BorrowedFd<'_>) -> Result<()> {
+        let err = unsafe {
+            libbpf_sys::bpf_prog_detach2(
+                self.as_fd().as_raw_fd(),
+                map.as_raw_fd(),
+                self.attach_type() as u32,
+            )
+        };
+        util::parse_ret(err)
+    }
+
+    /// Attach a verdict/parser to a [sockmap/sockhash](https://lwn.net/Articles/731133/)
+    /// using a [`Link`], which detaches the program once dropped.
+    ///
+    /// The attach type is determined as for
+    /// [`ProgramMut::attach_sockmap`].
+    pub fn attach_sockmap_link(&self, map: BorrowedFd<'_>) -> Result<Link> {
+        let ptr =
+            unsafe { libbpf_sys::bpf_program__attach_sockmap(self.ptr.as_ptr(), map.as_raw_fd()) };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach sockmap program")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
+    /// Attach this [`ProgramType::SkLookup`] program to the network
+    /// namespace referred to by `netns`, e.g., as opened from
+    /// `/proc/self/ns/net`.
+    pub fn attach_sk_lookup(&self, netns: BorrowedFd<'_>) -> Result<Link> {
+        let ptr =
+            unsafe { libbpf_sys::bpf_program__attach_netns(self.ptr.as_ptr(), netns.as_raw_fd()) };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach sk_lookup program")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
+    /// Attach this [`ProgramType::SockOps`] program to a cgroup, e.g., a
+    /// [`Cgroup`][crate::Cgroup].
+    pub fn attach_sockops(&self, cgroup: BorrowedFd<'_>) -> Result<Link> {
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_cgroup(self.ptr.as_ptr(), cgroup.as_raw_fd())
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach sockops program")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
     /// Attach this program to [XDP](https://lwn.net/Articles/825998/)
     pub fn attach_xdp(&self, ifindex: i32) -> Result<Link> {
         let ptr = unsafe { libbpf_sys::bpf_program__attach_xdp(self.ptr.as_ptr(), ifindex) };
@@ -1017,6 +1600,42 @@ impl<'obj> ProgramMut<'obj> {
         Ok(link)
     }
 
+    /// Attach this program to the netkit device with the given
+    /// interface index.
+    ///
+    /// Whether the program is attached to the primary or the peer
+    /// device of the pair is determined by the program's section, i.e.,
+    /// `SEC("netkit/primary")` or `SEC("netkit/peer")`. Multiple programs
+    /// can be attached in a well-defined order, as configured by `opts`.
+    pub fn attach_netkit(&self, ifindex: i32, opts: NetkitOpts) -> Result<Link> {
+        let opts = libbpf_sys::bpf_netkit_opts::from(opts);
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_netkit(self.ptr.as_ptr(), ifindex, &opts as *const _)
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach netkit program")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
+    /// Attach this program to the [tcx](https://lwn.net/Articles/938632/)
+    /// attachment point of the given interface.
+    ///
+    /// The direction is determined by the program's section, i.e.,
+    /// `SEC("tcx/ingress")` or `SEC("tcx/egress")`. Contrary to
+    /// [`TcHook`][crate::TcHook], multiple programs can be attached in a
+    /// well-defined order, as configured by `opts`.
+    pub fn attach_tcx(&self, ifindex: i32, opts: TcxOpts) -> Result<Link> {
+        let opts = libbpf_sys::bpf_tcx_opts::from(opts);
+        let ptr = unsafe {
+            libbpf_sys::bpf_program__attach_tcx(self.ptr.as_ptr(), ifindex, &opts as *const _)
+        };
+        let ptr = validate_bpf_ret(ptr).context("failed to attach tcx program")?;
+        // SAFETY: the pointer came from libbpf and has been checked for errors.
+        let link = unsafe { Link::new(ptr) };
+        Ok(link)
+    }
+
     fn attach_usdt_impl(
         &self,
         pid: i32,
@@ -1300,6 +1919,21 @@ mod tests {
             SkReuseportSelect,
             SkReuseportSelectOrMigrate,
             PerfEvent,
+            TraceKprobeMulti,
+            LsmCgroup,
+            StructOps,
+            Netfilter,
+            TcxIngress,
+            TcxEgress,
+            TraceUprobeMulti,
+            CgroupUnixConnect,
+            CgroupUnixSendmsg,
+            CgroupUnixRecvmsg,
+            CgroupUnixGetpeername,
+            CgroupUnixGetsockname,
+            NetkitPrimary,
+            NetkitPeer,
+            TraceKprobeSession,
             Unknown,
         ] {
             // check if discriminants match after a roundtrip conversion
@@ -1308,5 +1942,13 @@ mod tests {
                 discriminant(&ProgramAttachType::from(t as u32))
             );
         }
+
+        // check that implicit discriminants match the kernel's values
+        assert_eq!(TcxIngress as u32, libbpf_sys::BPF_TCX_INGRESS);
+        assert_eq!(NetkitPeer as u32, libbpf_sys::BPF_NETKIT_PEER);
+        assert_eq!(
+            TraceKprobeSession as u32,
+            libbpf_sys::BPF_TRACE_KPROBE_SESSION
+        );
     }
 }
@@ -13,6 +13,7 @@
 use std::ffi::c_void;
 use std::ffi::CString;
 use std::io;
+use std::mem;
 use std::mem::size_of_val;
 use std::os::fd::AsFd;
 use std::os::fd::AsRawFd;
@@ -24,6 +25,7 @@ use std::ptr;
 use std::time::Duration;
 
 use crate::util;
+use crate::Error;
 use crate::MapType;
 use crate::ProgramAttachType;
 use crate::ProgramType;
@@ -616,6 +618,46 @@ pub struct NetNsLinkInfo {
     pub attach_type: ProgramAttachType,
 }
 
+/// Information about a kprobe.multi link.
+#[derive(Debug, Clone)]
+pub struct KprobeMultiLinkInfo {
+    /// The number of functions the program is attached to.
+    pub count: u32,
+    /// Whether the program is attached as return probe.
+    pub retprobe: bool,
+    /// The number of times the program was not run because of
+    /// recursion.
+    pub missed: u64,
+}
+
+/// Information about a tcx link.
+#[derive(Debug, Clone)]
+pub struct TcxLinkInfo {
+    /// The index of the network interface the program is attached to.
+    pub ifindex: u32,
+    /// The attach type, i.e., the direction of traffic.
+    pub attach_type: ProgramAttachType,
+}
+
+/// Information about a netkit link.
+#[derive(Debug, Clone)]
+pub struct NetkitLinkInfo {
+    /// The index of the netkit device the program is attached to.
+    pub ifindex: u32,
+    /// The attach type, i.e., whether the program is attached to the
+    /// primary or the peer device.
+    pub attach_type: ProgramAttachType,
+}
+
+/// Information about a sockmap link.
+#[derive(Debug, Clone)]
+pub struct SockMapLinkInfo {
+    /// The ID of the sockmap or sockhash the program is attached to.
+    pub map_id: u32,
+    /// The attach type, e.g., stream parser or verdict.
+    pub attach_type: ProgramAttachType,
+}
+
 #[derive(Debug, Clone)]
 // TODO: Document variants.
 #[allow(missing_docs)]
@@ -625,6 +667,10 @@ pub enum LinkTypeInfo {
     Cgroup(CgroupLinkInfo),
     Iter,
     NetNs(NetNsLinkInfo),
+    KprobeMulti(KprobeMultiLinkInfo),
+    Tcx(TcxLinkInfo),
+    Netkit(NetkitLinkInfo),
+    SockMap(SockMapLinkInfo),
     Unknown,
 }
 
@@ -639,6 +685,21 @@ pub struct LinkInfo {
 }
 
 impl LinkInfo {
+    /// Retrieve information about the link referred to by `fd`.
+    pub(crate) fn load_from_fd(fd: BorrowedFd<'_>) -> Result<Self> {
+        // Padding bytes need to be zero, see `gen_info_impl`.
+        let mut item: libbpf_sys::bpf_link_info = unsafe { mem::zeroed() };
+        let item_ptr: *mut libbpf_sys::bpf_link_info = &mut item;
+        let mut len = size_of_val(&item) as u32;
+
+        let ret = unsafe {
+            libbpf_sys::bpf_obj_get_info_by_fd(fd.as_raw_fd(), item_ptr as *mut c_void, &mut len)
+        };
+        let () = util::parse_ret(ret)?;
+
+        Self::from_uapi(fd, item).ok_or_else(|| Error::from(io::Error::last_os_error()))
+    }
+
     fn from_uapi(fd: BorrowedFd<'_>, mut s: libbpf_sys::bpf_link_info) -> Option<Self> {
         let type_info = match s.type_ {
             libbpf_sys::BPF_LINK_TYPE_RAW_TRACEPOINT => {
@@ -684,6 +745,30 @@ impl LinkInfo {
                     s.__bindgen_anon_1.netns.attach_type
                 }),
             }),
+            libbpf_sys::BPF_LINK_TYPE_KPROBE_MULTI => {
+                let kprobe_multi = unsafe { s.__bindgen_anon_1.kprobe_multi };
+                LinkTypeInfo::KprobeMulti(KprobeMultiLinkInfo {
+                    count: kprobe_multi.count,
+                    retprobe: kprobe_multi.flags & libbpf_sys::BPF_F_KPROBE_MULTI_RETURN != 0,
+                    missed: kprobe_multi.missed,
+                })
+            }
+            libbpf_sys::BPF_LINK_TYPE_TCX => LinkTypeInfo::Tcx(TcxLinkInfo {
+                ifindex: unsafe { s.__bindgen_anon_1.tcx.ifindex },
+                attach_type: ProgramAttachType::from(unsafe { s.__bindgen_anon_1.tcx.attach_type }),
+            }),
+            libbpf_sys::BPF_LINK_TYPE_NETKIT => LinkTypeInfo::Netkit(NetkitLinkInfo {
+                ifindex: unsafe { s.__bindgen_anon_1.netkit.ifindex },
+                attach_type: ProgramAttachType::from(unsafe {
+                    s.__bindgen_anon_1.netkit.attach_type
+                }),
+            }),
+            libbpf_sys::BPF_LINK_TYPE_SOCKMAP => LinkTypeInfo::SockMap(SockMapLinkInfo {
+                map_id: unsafe { s.__bindgen_anon_1.sockmap.map_id },
+                attach_type: ProgramAttachType::from(unsafe {
+                    s.__bindgen_anon_1.sockmap.attach_type
+                }),
+            }),
             _ => LinkTypeInfo::Unknown,
         };
 
@@ -0,0 +1,203 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::mem::MaybeUninit;
+
+use crate::util;
+use crate::Error;
+use crate::MapCore;
+use crate::MapFlags;
+use crate::MapType;
+use crate::NoPadding;
+use crate::Result;
+
+/// Check that `map` is a keyless map of one of the given types with
+/// values of type `V`.
+fn check_keyless_map<V>(map: &dyn MapCore, types: &[MapType], what: &str) -> Result<()> {
+    let map_type = map.map_type();
+    if !types.contains(&map_type) {
+        return Err(Error::with_invalid_data(format!(
+            "map type {map_type:?} is not supported by {what}"
+        )));
+    }
+
+    if map.key_size() != 0 {
+        return Err(Error::with_invalid_data(format!(
+            "key_size {} != 0",
+            map.key_size()
+        )));
+    }
+
+    if map.value_size() as usize != size_of::<V>() {
+        return Err(Error::with_invalid_data(format!(
+            "value_size {} != {}",
+            map.value_size(),
+            size_of::<V>()
+        )));
+    }
+    Ok(())
+}
+
+/// A typed view on a [`MapType::Queue`] or [`MapType::Stack`] map.
+///
+/// Queues hand out values in FIFO order, stacks in LIFO order.
+pub struct QueueMap<'map, V> {
+    map: &'map dyn MapCore,
+    _phantom: PhantomData<fn(V) -> V>,
+}
+
+impl<'map, V> QueueMap<'map, V>
+where
+    V: NoPadding,
+{
+    /// Create a new `QueueMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::Queue`] or
+    ///   [`MapType::Stack`].
+    /// * If the map's value size does not match the size of `V`.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let () = check_keyless_map::<V>(map, &[MapType::Queue, MapType::Stack], "QueueMap")?;
+
+        Ok(Self {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Push a value.
+    ///
+    /// If the map is full, pushing fails, unless `flags` contains
+    /// [`MapFlags::EXIST`], in which case the oldest value gets
+    /// replaced.
+    pub fn push(&self, value: &V, flags: MapFlags) -> Result<()> {
+        self.map.update(&[], util::as_bytes(value), flags)
+    }
+
+    /// Pop a value, returning `None` if the map is empty.
+    pub fn pop(&self) -> Result<Option<V>> {
+        let value = self.map.lookup_and_delete(&[])?;
+        Ok(value.map(|value| util::from_bytes(&value)))
+    }
+
+    /// Retrieve the next value without removing it, returning `None`
+    /// if the map is empty.
+    pub fn peek(&self) -> Result<Option<V>> {
+        let mut value = MaybeUninit::<V>::uninit();
+        let found = self
+            .map
+            .lookup_uninit(&[], util::uninit_as_bytes_mut(&mut value), MapFlags::ANY)?
+            .is_some();
+        if found {
+            // SAFETY: The kernel initialized the entire value and any bit
+            //         pattern is valid for `NoPadding` types.
+            Ok(Some(unsafe { value.assume_init() }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns an iterator popping values until the map is empty.
+    ///
+    /// Failures to pop a value are reported as errors.
+    pub fn drain(&self) -> QueueDrain<'map, V> {
+        QueueDrain {
+            map: self.map,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<V> Debug for QueueMap<'_, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map, _phantom } = self;
+        f.debug_struct("QueueMap").field("map", map).finish()
+    }
+}
+
+/// A draining iterator over the values of a [`QueueMap`].
+pub struct QueueDrain<'map, V> {
+    map: &'map dyn MapCore,
+    _phantom: PhantomData<fn() -> V>,
+}
+
+impl<V> Iterator for QueueDrain<'_, V>
+where
+    V: NoPadding,
+{
+    type Item = Result<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.map.lookup_and_delete(&[]) {
+            Ok(Some(value)) => Some(Ok(util::from_bytes(&value))),
+            // The map is empty.
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl<V> Debug for QueueDrain<'_, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map, _phantom } = self;
+        f.debug_struct("QueueDrain").field("map", map).finish()
+    }
+}
+
+/// A typed view on a [`MapType::BloomFilter`] map.
+pub struct BloomFilterMap<'map, V> {
+    map: &'map dyn MapCore,
+    _phantom: PhantomData<fn(V)>,
+}
+
+impl<'map, V> BloomFilterMap<'map, V>
+where
+    V: NoPadding,
+{
+    /// Create a new `BloomFilterMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::BloomFilter`].
+    /// * If the map's value size does not match the size of `V`.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let () = check_keyless_map::<V>(map, &[MapType::BloomFilter], "BloomFilterMap")?;
+
+        Ok(Self {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Add a value to the bloom filter.
+    pub fn push(&self, value: &V) -> Result<()> {
+        self.map.update(&[], util::as_bytes(value), MapFlags::ANY)
+    }
+
+    /// Check whether the bloom filter (likely) contains `value`.
+    ///
+    /// False positives are possible, false negatives are not.
+    pub fn contains(&self, value: &V) -> Result<bool> {
+        self.map.lookup_bloom_filter(util::as_bytes(value))
+    }
+}
+
+impl<V> Debug for BloomFilterMap<'_, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map, _phantom } = self;
+        f.debug_struct("BloomFilterMap").field("map", map).finish()
+    }
+}
@@ -2,16 +2,25 @@ use core::ffi::c_void;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::io;
+use std::marker::PhantomData;
 use std::ops::Deref as _;
 use std::ops::DerefMut as _;
 use std::os::raw::c_ulong;
 use std::os::unix::prelude::AsRawFd;
 use std::os::unix::prelude::BorrowedFd;
+#[cfg(feature = "tokio")]
+use std::os::unix::prelude::RawFd;
 use std::ptr::null_mut;
 use std::ptr::NonNull;
 use std::slice;
 use std::time::Duration;
 
+#[cfg(feature = "tokio")]
+use tokio::io::unix::AsyncFd;
+#[cfg(feature = "tokio")]
+use tokio::io::Interest;
+
 use crate::util;
 use crate::util::validate_bpf_ret;
 use crate::AsRawLibbpf;
@@ -135,13 +144,24 @@ impl<'slf, 'cb: 'slf> RingBufferBuilder<'slf, 'cb> {
         }
 
         match rb_ptr {
-            Some(ptr) => Ok(RingBuffer { ptr, _cbs: cbs }),
+            Some(ptr) => Ok(RingBuffer { ptr, cbs }),
             None => Err(Error::with_invalid_data(
                 "You must add at least one ring buffer map and callback before building",
             )),
         }
     }
 
+    /// Build a new [`AsyncRingBuffer`]. Must have added at least one
+    /// ringbuf.
+    ///
+    /// This method has to be called from within the context of a
+    /// `tokio` runtime with IO enabled.
+    #[cfg(feature = "tokio")]
+    pub fn build_async(self) -> Result<AsyncRingBuffer<'cb>> {
+        let rb = self.build()?;
+        AsyncRingBuffer::new(rb)
+    }
+
     unsafe extern "C" fn call_sample_cb(ctx: *mut c_void, data: *mut c_void, size: c_ulong) -> i32 {
         let callback_struct = ctx as *mut RingBufferCallback<'_>;
         let callback = unsafe { (*callback_struct).cb.as_mut() };
@@ -160,7 +180,7 @@ impl<'slf, 'cb: 'slf> RingBufferBuilder<'slf, 'cb> {
 pub struct RingBuffer<'cb> {
     ptr: NonNull<libbpf_sys::ring_buffer>,
     #[allow(clippy::vec_box)]
-    _cbs: Vec<Box<RingBufferCallback<'cb>>>,
+    cbs: Vec<Box<RingBufferCallback<'cb>>>,
 }
 
 impl RingBuffer<'_> {
@@ -207,10 +227,80 @@ impl RingBuffer<'_> {
         util::parse_ret(ret)
     }
 
+    /// Greedily consume up to `n` events from all open ring buffers,
+    /// calling the registered callback for each one. Consumption stops
+    /// early if we run out of events or one of the callbacks returns a
+    /// non-zero integer.
+    ///
+    /// Return the amount of events consumed.
+    pub fn consume_n(&self, n: usize) -> Result<usize> {
+        let ret = unsafe {
+            libbpf_sys::ring_buffer__consume_n(self.ptr.as_ptr(), n as libbpf_sys::size_t)
+        };
+        util::parse_ret_i32(ret).map(|count| count as usize)
+    }
+
+    /// Wait until data is available in any of the open ring buffers or
+    /// `timeout` is reached and then consume up to `n` events, calling
+    /// the registered callback for each one. If `timeout` is
+    /// Duration::MAX, this will block indefinitely until an event
+    /// occurs.
+    ///
+    /// Contrary to [`RingBuffer::poll`], this method bounds the amount
+    /// of work performed per invocation. Return the amount of events
+    /// consumed.
+    pub fn poll_n(&self, timeout: Duration, n: usize) -> Result<usize> {
+        let mut timeout_ms = -1;
+        if timeout != Duration::MAX {
+            timeout_ms = timeout.as_millis() as i32;
+        }
+
+        let mut pollfd = libc::pollfd {
+            fd: self.epoll_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ret = unsafe { libc::poll(&mut pollfd, 1, timeout_ms) };
+        match ret {
+            ret if ret < 0 => {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    Ok(0)
+                } else {
+                    Err(Error::from(err))
+                }
+            }
+            0 => Ok(0),
+            _ => self.consume_n(n),
+        }
+    }
+
     /// Get an fd that can be used to sleep until data is available
     pub fn epoll_fd(&self) -> i32 {
         unsafe { libbpf_sys::ring_buffer__epoll_fd(self.ptr.as_ptr()) }
     }
+
+    /// Retrieve the [`Ring`] for the ring buffer map at index `idx`,
+    /// in the order in which maps were added to the
+    /// [`RingBufferBuilder`].
+    pub fn ring(&self, idx: usize) -> Option<Ring<'_>> {
+        if idx >= self.cbs.len() {
+            return None
+        }
+
+        let ptr = unsafe { libbpf_sys::ring_buffer__ring(self.ptr.as_ptr(), idx as _) };
+        NonNull::new(ptr).map(|ptr| Ring {
+            ptr,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns an iterator over the [`Ring`]s of all ring buffer maps,
+    /// in the order in which they were added to the
+    /// [`RingBufferBuilder`].
+    pub fn rings(&self) -> impl Iterator<Item = Ring<'_>> {
+        (0..self.cbs.len()).filter_map(|idx| self.ring(idx))
+    }
 }
 
 impl AsRawLibbpf for RingBuffer<'_> {
@@ -233,6 +323,141 @@ impl Drop for RingBuffer<'_> {
     }
 }
 
+/// A single ring buffer map of a [`RingBuffer`].
+///
+/// A `Ring` provides access to the state of the ring, such as its fill
+/// level, and allows for consuming events from just this ring.
+#[derive(Debug)]
+pub struct Ring<'rb> {
+    ptr: NonNull<libbpf_sys::ring>,
+    _phantom: PhantomData<&'rb libbpf_sys::ring_buffer>,
+}
+
+impl Ring<'_> {
+    /// Retrieve the current consumer position of the ring.
+    pub fn consumer_pos(&self) -> u64 {
+        unsafe { libbpf_sys::ring__consumer_pos(self.ptr.as_ptr()) as u64 }
+    }
+
+    /// Retrieve the current producer position of the ring.
+    pub fn producer_pos(&self) -> u64 {
+        unsafe { libbpf_sys::ring__producer_pos(self.ptr.as_ptr()) as u64 }
+    }
+
+    /// Retrieve the number of bytes in the ring that have not yet been
+    /// consumed.
+    pub fn avail_data_size(&self) -> usize {
+        unsafe { libbpf_sys::ring__avail_data_size(self.ptr.as_ptr()) as usize }
+    }
+
+    /// Retrieve the total size of the ring in bytes.
+    pub fn size(&self) -> usize {
+        unsafe { libbpf_sys::ring__size(self.ptr.as_ptr()) as usize }
+    }
+
+    /// Retrieve the file descriptor of the ring buffer map backing this
+    /// ring.
+    pub fn map_fd(&self) -> BorrowedFd<'_> {
+        let fd = unsafe { libbpf_sys::ring__map_fd(self.ptr.as_ptr()) };
+        // SAFETY: The file descriptor is valid for as long as the ring
+        //         buffer exists.
+        unsafe { BorrowedFd::borrow_raw(fd) }
+    }
+
+    /// Greedily consume from this ring, calling the registered callback
+    /// for each event.
+    ///
+    /// Return the amount of events consumed.
+    pub fn consume(&self) -> Result<usize> {
+        let ret = unsafe { libbpf_sys::ring__consume(self.ptr.as_ptr()) };
+        util::parse_ret_i32(ret).map(|count| count as usize)
+    }
+
+    /// Greedily consume up to `n` events from this ring, calling the
+    /// registered callback for each one.
+    ///
+    /// Return the amount of events consumed.
+    pub fn consume_n(&self, n: usize) -> Result<usize> {
+        let ret =
+            unsafe { libbpf_sys::ring__consume_n(self.ptr.as_ptr(), n as libbpf_sys::size_t) };
+        util::parse_ret_i32(ret).map(|count| count as usize)
+    }
+}
+
+impl AsRawLibbpf for Ring<'_> {
+    type LibbpfType = libbpf_sys::ring;
+
+    /// Retrieve the underlying [`libbpf_sys::ring`].
+    fn as_libbpf_object(&self) -> NonNull<Self::LibbpfType> {
+        self.ptr
+    }
+}
+
+/// A [`RingBuffer`] that can be consumed asynchronously.
+///
+/// An `AsyncRingBuffer` registers the ring buffer's epoll file
+/// descriptor with the `tokio` reactor, so that tasks can wait for
+/// data without blocking a thread. It is available with the `tokio`
+/// feature.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncRingBuffer<'cb> {
+    // NB: Has to be dropped before `rb`, so that the epoll file
+    //     descriptor is deregistered before it gets closed.
+    fd: AsyncFd<RawFd>,
+    rb: RingBuffer<'cb>,
+}
+
+#[cfg(feature = "tokio")]
+impl<'cb> AsyncRingBuffer<'cb> {
+    /// Create a new `AsyncRingBuffer` from a [`RingBuffer`].
+    ///
+    /// This function has to be called from within the context of a
+    /// `tokio` runtime with IO enabled.
+    pub fn new(rb: RingBuffer<'cb>) -> Result<Self> {
+        let fd = AsyncFd::with_interest(rb.epoll_fd(), Interest::READABLE)
+            .context("failed to register ring buffer with tokio reactor")?;
+        Ok(Self { fd, rb })
+    }
+
+    /// Wait until data is available in any of the ring buffers and
+    /// greedily consume it, calling the registered callbacks.
+    ///
+    /// This method is cancellation safe: if the returned future is
+    /// dropped before completion, no data will have been consumed.
+    pub async fn consume(&mut self) -> Result<()> {
+        loop {
+            let mut guard = self.fd.readable().await?;
+            let ret = self.rb.consume_raw();
+            if ret < 0 {
+                // A callback stopped consumption early or an error
+                // occurred. Data may still be available, so don't
+                // clear the readiness state.
+                return Err(Error::from_raw_os_error(-ret))
+            }
+
+            let () = guard.clear_ready();
+            if ret > 0 {
+                break Ok(())
+            }
+        }
+    }
+
+    /// Retrieve a reference to the underlying [`RingBuffer`].
+    #[inline]
+    pub fn get_ref(&self) -> &RingBuffer<'cb> {
+        &self.rb
+    }
+
+    /// Deregister the ring buffer from the `tokio` reactor and
+    /// retrieve the underlying [`RingBuffer`].
+    pub fn into_inner(self) -> RingBuffer<'cb> {
+        let Self { fd, rb } = self;
+        let _fd = fd.into_inner();
+        rb
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,237 @@
+use std::io;
+use std::ops::Deref;
+use std::os::unix::io::AsFd as _;
+use std::os::unix::io::AsRawFd as _;
+use std::os::unix::io::BorrowedFd;
+use std::ptr;
+use std::ptr::NonNull;
+use std::slice;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use crate::util;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::MapCore;
+use crate::MapType;
+use crate::Result;
+
+// from kernel @ include/uapi/linux/bpf.h
+const BPF_RINGBUF_BUSY_BIT: u32 = 1 << 31;
+const BPF_RINGBUF_DISCARD_BIT: u32 = 1 << 30;
+const BPF_RINGBUF_HDR_SZ: usize = 8;
+
+/// Memory map `len` bytes of the file referred to by `fd` at `offset`.
+fn mmap(fd: BorrowedFd<'_>, len: usize, prot: i32, offset: usize) -> Result<NonNull<u8>> {
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            prot,
+            libc::MAP_SHARED,
+            fd.as_raw_fd(),
+            offset as libc::off_t,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(Error::from(io::Error::last_os_error()))
+    }
+    // SAFETY: `mmap` never returns NULL on success.
+    Ok(unsafe { NonNull::new_unchecked(ptr.cast()) })
+}
+
+/// A pull-based reader of a [`MapType::RingBuf`] map.
+///
+/// Contrary to [`RingBuffer`][crate::RingBuffer], which invokes
+/// callbacks for events, a `RingBufferReader` memory maps the ring
+/// buffer itself and hands out [`RingBufferSample`]s on request. A
+/// sample is marked as consumed once it is dropped, allowing for the
+/// application of backpressure by simply not requesting the next
+/// sample. Records discarded by the producer are skipped transparently.
+#[derive(Debug)]
+pub struct RingBufferReader<'map> {
+    fd: BorrowedFd<'map>,
+    consumer: NonNull<u8>,
+    producer: NonNull<u8>,
+    mask: usize,
+    page_size: usize,
+}
+
+impl<'map> RingBufferReader<'map> {
+    /// Create a new `RingBufferReader` for the provided ring buffer map.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let map_type = map.map_type();
+        if map_type != MapType::RingBuf {
+            return Err(Error::with_invalid_data(format!(
+                "map type {map_type:?} is not a ring buffer"
+            )));
+        }
+
+        let max_entries = map.info()?.info.max_entries as usize;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let fd = map.as_fd();
+
+        // The consumer position is located in the first page, which is
+        // writable from user space.
+        let consumer = mmap(fd, page_size, libc::PROT_READ | libc::PROT_WRITE, 0)
+            .context("failed to mmap ring buffer consumer page")?;
+        // The producer position is located in the second page, followed
+        // by the data pages, which are mapped twice in a row, so that
+        // records wrapping around the end of the ring are contiguous.
+        let producer = match mmap(fd, page_size + 2 * max_entries, libc::PROT_READ, page_size) {
+            Ok(producer) => producer,
+            Err(err) => {
+                let _ret = unsafe { libc::munmap(consumer.as_ptr().cast(), page_size) };
+                return Err(err).context("failed to mmap ring buffer producer and data pages")
+            }
+        };
+
+        Ok(Self {
+            fd,
+            consumer,
+            producer,
+            mask: max_entries - 1,
+            page_size,
+        })
+    }
+
+    #[inline]
+    fn consumer_pos(&self) -> &AtomicUsize {
+        // SAFETY: The consumer position is an `unsigned long` at the
+        //         start of the page aligned consumer page.
+        unsafe { &*self.consumer.as_ptr().cast::<AtomicUsize>() }
+    }
+
+    #[inline]
+    fn producer_pos(&self) -> &AtomicUsize {
+        // SAFETY: The producer position is an `unsigned long` at the
+        //         start of the page aligned producer page.
+        unsafe { &*self.producer.as_ptr().cast::<AtomicUsize>() }
+    }
+
+    #[inline]
+    fn data(&self) -> *const u8 {
+        // SAFETY: The data pages directly follow the producer page.
+        unsafe { self.producer.as_ptr().add(self.page_size) }
+    }
+
+    /// Retrieve the number of bytes in the ring buffer that have not yet
+    /// been consumed.
+    pub fn avail_data_size(&self) -> usize {
+        let cons_pos = self.consumer_pos().load(Ordering::Acquire);
+        let prod_pos = self.producer_pos().load(Ordering::Acquire);
+        prod_pos.wrapping_sub(cons_pos)
+    }
+
+    /// Wait until data is available or `timeout` is reached. If
+    /// `timeout` is Duration::MAX, this will block indefinitely until
+    /// data is available.
+    ///
+    /// Return whether data is available.
+    pub fn wait(&self, timeout: Duration) -> Result<bool> {
+        let mut timeout_ms = -1;
+        if timeout != Duration::MAX {
+            timeout_ms = timeout.as_millis() as i32;
+        }
+
+        let mut pollfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ret = unsafe { libc::poll(&mut pollfd, 1, timeout_ms) };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                return Ok(false)
+            }
+            return Err(Error::from(err))
+        }
+        Ok(ret > 0)
+    }
+
+    /// Retrieve the next sample, if any.
+    ///
+    /// The sample is marked as consumed once it is dropped. Records
+    /// that have been discarded by the producer are skipped. `None` is
+    /// returned if no committed sample is available.
+    // A `RingBufferReader` cannot implement `Iterator`, as samples
+    // borrow from the reader.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<RingBufferSample<'_>> {
+        let mut cons_pos = self.consumer_pos().load(Ordering::Acquire);
+        loop {
+            let prod_pos = self.producer_pos().load(Ordering::Acquire);
+            if cons_pos >= prod_pos {
+                return None
+            }
+
+            // SAFETY: The record header is located within the data
+            //         pages and is four byte aligned.
+            let hdr = unsafe { self.data().add(cons_pos & self.mask) };
+            let len = unsafe { &*hdr.cast::<AtomicU32>() }.load(Ordering::Acquire);
+            if len & BPF_RINGBUF_BUSY_BIT != 0 {
+                // The producer has not yet committed the record.
+                return None
+            }
+
+            let sample_len = (len & !BPF_RINGBUF_DISCARD_BIT) as usize;
+            let next_pos = cons_pos + util::roundup(sample_len + BPF_RINGBUF_HDR_SZ, 8);
+
+            if len & BPF_RINGBUF_DISCARD_BIT != 0 {
+                let () = self.consumer_pos().store(next_pos, Ordering::Release);
+                cons_pos = next_pos;
+                continue
+            }
+
+            // SAFETY: The sample is located within the data pages, which
+            //         are mapped twice in a row, and remains valid until
+            //         the consumer position is advanced past it.
+            let data = unsafe { slice::from_raw_parts(hdr.add(BPF_RINGBUF_HDR_SZ), sample_len) };
+            break Some(RingBufferSample {
+                data,
+                consumer_pos: self.consumer_pos(),
+                next_pos,
+            })
+        }
+    }
+}
+
+impl Drop for RingBufferReader<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            let _ret = libc::munmap(self.consumer.as_ptr().cast(), self.page_size);
+            let _ret = libc::munmap(
+                self.producer.as_ptr().cast(),
+                self.page_size + 2 * (self.mask + 1),
+            );
+        }
+    }
+}
+
+/// A sample read from a [`RingBufferReader`].
+///
+/// The sample dereferences to its data and is marked as consumed when
+/// dropped.
+#[derive(Debug)]
+pub struct RingBufferSample<'rb> {
+    data: &'rb [u8],
+    consumer_pos: &'rb AtomicUsize,
+    next_pos: usize,
+}
+
+impl Deref for RingBufferSample<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl Drop for RingBufferSample<'_> {
+    fn drop(&mut self) {
+        let () = self.consumer_pos.store(self.next_pos, Ordering::Release);
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::os::unix::io::AsFd;
+use std::os::unix::io::AsRawFd as _;
+
+use crate::util;
+use crate::Error;
+use crate::Link;
+use crate::MapCore;
+use crate::MapFlags;
+use crate::MapType;
+use crate::NoPadding;
+use crate::ProgramMut;
+use crate::Result;
+
+/// Retrieve the cookie of the given socket.
+///
+/// Socket cookies uniquely identify a socket for the lifetime of the
+/// system. They are what lookups in a [`SockMap`] or [`SockHash`]
+/// report and what BPF programs see as `bpf_get_socket_cookie()`.
+pub fn socket_cookie(socket: impl AsFd) -> Result<u64> {
+    let mut cookie = 0u64;
+    let mut len = size_of::<u64>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            socket.as_fd().as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_COOKIE,
+            &mut cookie as *mut u64 as *mut _,
+            &mut len,
+        )
+    };
+    if ret < 0 {
+        return Err(Error::from(io::Error::last_os_error()))
+    }
+    Ok(cookie)
+}
+
+/// Insert `socket` into `map` under `key`.
+fn insert_socket(map: &dyn MapCore, key: &[u8], socket: impl AsFd, flags: MapFlags) -> Result<()> {
+    let fd = socket.as_fd().as_raw_fd();
+    // Sockets are inserted by file descriptor, using the map's value
+    // size.
+    if map.value_size() as usize == size_of::<u64>() {
+        map.update(key, &(fd as u64).to_ne_bytes(), flags)
+    } else {
+        map.update(key, &(fd as u32).to_ne_bytes(), flags)
+    }
+}
+
+/// Look up the cookie of the socket stored in `map` under `key`.
+fn lookup_cookie(map: &dyn MapCore, key: &[u8]) -> Result<Option<u64>> {
+    if map.value_size() as usize != size_of::<u64>() {
+        return Err(Error::with_invalid_data(
+            "socket cookies can only be looked up in maps with 8 byte values",
+        ));
+    }
+
+    let mut cookie = [0; size_of::<u64>()];
+    let found = map.lookup_into(key, &mut cookie, MapFlags::ANY)?;
+    Ok(found.then(|| u64::from_ne_bytes(cookie)))
+}
+
+/// A typed view on a [`MapType::Sockmap`] map.
+///
+/// Sockets, e.g., [`TcpStream`][std::net::TcpStream]s, are inserted by
+/// file descriptor at a given index. Lookups report the socket's
+/// cookie, as also returned by [`socket_cookie`]. `sk_msg` and `sk_skb`
+/// programs attached to the map using [`SockMap::attach_link`] can
+/// redirect traffic to the sockets of the map, e.g., using the
+/// `bpf_msg_redirect_map()` and `bpf_sk_redirect_map()` helpers.
+pub struct SockMap<'map> {
+    map: &'map dyn MapCore,
+}
+
+impl<'map> SockMap<'map> {
+    /// Create a new `SockMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::Sockmap`].
+    /// * If the map's key is not four bytes large or its value is
+    ///   neither four nor eight bytes large.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let () = util::check_fd_map(
+            map,
+            &[MapType::Sockmap],
+            size_of::<u32>(),
+            &[4, 8],
+            "SockMap",
+        )?;
+        Ok(Self { map })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Insert `socket` at `index`.
+    ///
+    /// The map takes a reference to the socket itself, i.e., the socket
+    /// stays in the map even if `socket` is closed.
+    pub fn insert(&self, index: u32, socket: impl AsFd, flags: MapFlags) -> Result<()> {
+        insert_socket(self.map, &index.to_ne_bytes(), socket, flags)
+    }
+
+    /// Look up the cookie of the socket at `index`, returning `None` if
+    /// the slot is empty.
+    ///
+    /// # Errors
+    /// * If the map's value is not eight bytes large.
+    pub fn cookie(&self, index: u32) -> Result<Option<u64>> {
+        lookup_cookie(self.map, &index.to_ne_bytes())
+    }
+
+    /// Remove the socket at `index`.
+    pub fn remove(&self, index: u32) -> Result<()> {
+        self.map.delete(&index.to_ne_bytes())
+    }
+
+    /// Attach an `sk_msg` or `sk_skb` program to the map using a
+    /// [`Link`], which detaches the program once dropped.
+    ///
+    /// Verdict programs attached this way see the traffic of all
+    /// sockets in the map and can redirect it to any of them.
+    pub fn attach_link(&self, prog: &ProgramMut<'_>) -> Result<Link> {
+        prog.attach_sockmap_link(self.map.as_fd())
+    }
+}
+
+impl Debug for SockMap<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map } = self;
+        f.debug_struct("SockMap").field("map", map).finish()
+    }
+}
+
+/// A typed view on a [`MapType::Sockhash`] map with keys of type `K`.
+///
+/// Contrary to [`SockMap`], sockets are inserted under arbitrary keys,
+/// e.g., a connection's 4-tuple. Programs attached using
+/// [`SockHash::attach_link`] can redirect traffic to the sockets of the
+/// map, e.g., using the `bpf_msg_redirect_hash()` and
+/// `bpf_sk_redirect_hash()` helpers.
+pub struct SockHash<'map, K> {
+    map: &'map dyn MapCore,
+    _phantom: PhantomData<fn(&K)>,
+}
+
+impl<'map, K> SockHash<'map, K>
+where
+    K: NoPadding,
+{
+    /// Create a new `SockHash` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::Sockhash`].
+    /// * If the map's key size does not match the size of `K` or its
+    ///   value is neither four nor eight bytes large.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let () = util::check_fd_map(
+            map,
+            &[MapType::Sockhash],
+            size_of::<K>(),
+            &[4, 8],
+            "SockHash",
+        )?;
+        Ok(Self {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Insert `socket` under `key`.
+    ///
+    /// The map takes a reference to the socket itself, i.e., the socket
+    /// stays in the map even if `socket` is closed.
+    pub fn insert(&self, key: &K, socket: impl AsFd, flags: MapFlags) -> Result<()> {
+        insert_socket(self.map, util::as_bytes(key), socket, flags)
+    }
+
+    /// Look up the cookie of the socket stored under `key`, returning
+    /// `None` if there is none.
+    ///
+    /// # Errors
+    /// * If the map's value is not eight bytes large.
+    pub fn cookie(&self, key: &K) -> Result<Option<u64>> {
+        lookup_cookie(self.map, util::as_bytes(key))
+    }
+
+    /// Remove the socket stored under `key`.
+    pub fn remove(&self, key: &K) -> Result<()> {
+        self.map.delete(util::as_bytes(key))
+    }
+
+    /// Attach an `sk_msg` or `sk_skb` program to the map using a
+    /// [`Link`], which detaches the program once dropped.
+    ///
+    /// Verdict programs attached this way see the traffic of all
+    /// sockets in the map and can redirect it to any of them.
+    pub fn attach_link(&self, prog: &ProgramMut<'_>) -> Result<Link> {
+        prog.attach_sockmap_link(self.map.as_fd())
+    }
+}
+
+impl<K> Debug for SockHash<'_, K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map, _phantom } = self;
+        f.debug_struct("SockHash").field("map", map).finish()
+    }
+}
@@ -0,0 +1,88 @@
+use std::mem::size_of;
+
+use crate::mprog;
+use crate::MprogPosition;
+use crate::MprogProgs;
+use crate::ProgramAttachType;
+use crate::Result;
+
+/// The direction of traffic a tcx program is attached for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TcxDirection {
+    /// Ingress traffic.
+    Ingress,
+    /// Egress traffic.
+    Egress,
+}
+
+impl From<TcxDirection> for ProgramAttachType {
+    fn from(direction: TcxDirection) -> Self {
+        match direction {
+            TcxDirection::Ingress => ProgramAttachType::TcxIngress,
+            TcxDirection::Egress => ProgramAttachType::TcxEgress,
+        }
+    }
+}
+
+/// Options to be provided when attaching a program to a tcx attachment
+/// point.
+#[derive(Clone, Debug, Default)]
+pub struct TcxOpts {
+    /// The position at which to insert the program relative to the
+    /// programs already attached.
+    pub position: MprogPosition,
+    /// If non-zero, only attach the program if the revision of the
+    /// attachment point matches, as reported by [`Tcx::query`].
+    pub expected_revision: u64,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl From<TcxOpts> for libbpf_sys::bpf_tcx_opts {
+    fn from(opts: TcxOpts) -> Self {
+        let TcxOpts {
+            position,
+            expected_revision,
+            _non_exhaustive,
+        } = opts;
+        let (flags, relative_fd, relative_id) = position.to_raw();
+
+        #[allow(clippy::needless_update)]
+        libbpf_sys::bpf_tcx_opts {
+            sz: size_of::<Self>() as _,
+            flags,
+            relative_fd,
+            relative_id,
+            expected_revision,
+            ..Default::default()
+        }
+    }
+}
+
+/// A tcx attachment point, i.e., the link-based traffic control hook
+/// of a network interface for a given direction.
+///
+/// Programs are attached to a tcx attachment point using
+/// [`ProgramMut::attach_tcx`][crate::ProgramMut::attach_tcx]. The
+/// direction is determined by the program's section, i.e.,
+/// `SEC("tcx/ingress")` or `SEC("tcx/egress")`.
+#[derive(Clone, Copy, Debug)]
+pub struct Tcx {
+    ifindex: i32,
+    direction: TcxDirection,
+}
+
+impl Tcx {
+    /// Create a new `Tcx` instance for the given interface and
+    /// direction.
+    pub fn new(ifindex: i32, direction: TcxDirection) -> Self {
+        Self { ifindex, direction }
+    }
+
+    /// Query the programs attached to the attachment point, along with
+    /// its revision.
+    pub fn query(&self) -> Result<MprogProgs> {
+        let attach_type = ProgramAttachType::from(self.direction);
+        mprog::query(self.ifindex, attach_type as u32)
+    }
+}
@@ -0,0 +1,393 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::mem::MaybeUninit;
+
+use crate::map::BatchedMapIter;
+use crate::util;
+use crate::util::validate_bpf_ret;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::MapCore;
+use crate::MapFlags;
+use crate::MapKeyIter;
+use crate::MapType;
+use crate::NoPadding;
+use crate::Result;
+
+/// Check that the BTF type `type_id` of the BTF object `btf_id` has a
+/// size of `size` bytes.
+fn check_btf_type_size(btf_id: u32, type_id: u32, size: usize, what: &str) -> Result<()> {
+    let btf = unsafe { libbpf_sys::btf__load_from_kernel_by_id(btf_id) };
+    let btf = validate_bpf_ret(btf).context("failed to load map BTF from kernel")?;
+    // SAFETY: `btf` is a valid pointer checked above.
+    let btf_size = unsafe { libbpf_sys::btf__resolve_size(btf.as_ptr(), type_id) };
+    // SAFETY: `btf` is a valid pointer and no longer used after this
+    //         call.
+    let () = unsafe { libbpf_sys::btf__free(btf.as_ptr()) };
+
+    if btf_size < 0 {
+        return Err(Error::from_raw_os_error(-btf_size as i32))
+            .with_context(|| format!("failed to resolve size of BTF {what} type {type_id}"));
+    }
+
+    if btf_size as usize != size {
+        return Err(Error::with_invalid_data(format!(
+            "BTF {what} type size {btf_size} != {size}"
+        )));
+    }
+    Ok(())
+}
+
+/// Check that the key and value sizes of `map` (and its BTF key and
+/// value types, if available) match `K` and `V`.
+pub(crate) fn check_map_layout<K, V>(map: &dyn MapCore) -> Result<()> {
+    if map.key_size() as usize != size_of::<K>() {
+        return Err(Error::with_invalid_data(format!(
+            "key_size {} != {}",
+            map.key_size(),
+            size_of::<K>()
+        )));
+    }
+
+    if map.value_size() as usize != size_of::<V>() {
+        return Err(Error::with_invalid_data(format!(
+            "value_size {} != {}",
+            map.value_size(),
+            size_of::<V>()
+        )));
+    }
+
+    let info = map.info()?.info;
+    if info.btf_id != 0 {
+        if info.btf_key_type_id != 0 {
+            let () = check_btf_type_size(info.btf_id, info.btf_key_type_id, size_of::<K>(), "key")?;
+        }
+        if info.btf_value_type_id != 0 {
+            let () =
+                check_btf_type_size(info.btf_id, info.btf_value_type_id, size_of::<V>(), "value")?;
+        }
+    }
+    Ok(())
+}
+
+/// A typed view on a [`MapCore`] object.
+///
+/// A `TypedMap` validates the map's key and value sizes (and BTF key
+/// and value types, if available) against `K` and `V` once, when it is
+/// created, and then works directly with `K` and `V` instead of raw
+/// bytes.
+///
+/// Supported are maps of type [`MapType::Hash`], [`MapType::Array`],
+/// [`MapType::LruHash`], and [`MapType::LpmTrie`].
+///
+/// Keys and values are passed to the kernel as their raw in-memory
+/// representation. Hence, `K` and `V` have to be free of padding bytes,
+/// as expressed by the [`NoPadding`] trait.
+///
+/// ```no_run
+/// # use libbpf_rs::MapHandle;
+/// # use libbpf_rs::MapFlags;
+/// # use libbpf_rs::TypedMap;
+/// # let get_map = || -> &MapHandle { todo!() };
+/// let map: &MapHandle = get_map();
+/// let map = TypedMap::<u32, u64>::new(map).unwrap();
+/// let () = map.insert(&1, &42, MapFlags::ANY).unwrap();
+/// assert_eq!(map.get(&1).unwrap(), Some(42));
+/// ```
+pub struct TypedMap<'map, K, V> {
+    map: &'map dyn MapCore,
+    _phantom: PhantomData<fn(K) -> V>,
+}
+
+impl<'map, K, V> TypedMap<'map, K, V>
+where
+    K: NoPadding,
+    V: NoPadding,
+{
+    /// Create a new `TypedMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of a supported type.
+    /// * If the map's key or value size does not match the size of `K`
+    ///   or `V`, respectively.
+    /// * If the map has BTF type information associated with it and
+    ///   the key or value types' sizes do not match those of `K` or
+    ///   `V`, respectively.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let map_type = map.map_type();
+        if !matches!(
+            map_type,
+            MapType::Hash | MapType::Array | MapType::LruHash | MapType::LpmTrie
+        ) {
+            return Err(Error::with_invalid_data(format!(
+                "map type {map_type:?} is not supported by TypedMap"
+            )));
+        }
+
+        let () = check_map_layout::<K, V>(map)?;
+
+        Ok(Self {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Look up the value associated with `key`.
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        self.get_with_flags(key, MapFlags::ANY)
+    }
+
+    /// Look up the value associated with `key`, providing additional
+    /// lookup flags.
+    pub fn get_with_flags(&self, key: &K, flags: MapFlags) -> Result<Option<V>> {
+        let mut value = MaybeUninit::<V>::uninit();
+        let found = self
+            .map
+            .lookup_uninit(
+                util::as_bytes(key),
+                util::uninit_as_bytes_mut(&mut value),
+                flags,
+            )?
+            .is_some();
+        if found {
+            // SAFETY: The kernel initialized the entire value and any bit
+            //         pattern is valid for `NoPadding` types.
+            Ok(Some(unsafe { value.assume_init() }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Check whether the map contains a value for `key`.
+    pub fn contains_key(&self, key: &K) -> Result<bool> {
+        self.get(key).map(|value| value.is_some())
+    }
+
+    /// Insert or update the value associated with `key`.
+    pub fn insert(&self, key: &K, value: &V, flags: MapFlags) -> Result<()> {
+        self.map
+            .update(util::as_bytes(key), util::as_bytes(value), flags)
+    }
+
+    /// Remove the value associated with `key`.
+    pub fn remove(&self, key: &K) -> Result<()> {
+        self.map.delete(util::as_bytes(key))
+    }
+
+    /// Returns an iterator over the keys in this map.
+    ///
+    /// Please refer to [`MapCore::keys`] for caveats concerning
+    /// concurrent modification of the map.
+    pub fn keys(&self) -> TypedKeyIter<'map, K> {
+        TypedKeyIter {
+            iter: self.map.keys(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over key-value pairs in this map.
+    ///
+    /// Values are looked up as the iteration progresses, with failed
+    /// lookups being reported as errors. Keys that got removed
+    /// concurrently are skipped. Please refer to
+    /// [`MapCore::keys`] for additional caveats concerning concurrent
+    /// modification of the map.
+    pub fn iter(&self) -> TypedMapIter<'map, K, V> {
+        TypedMapIter {
+            map: self.map,
+            keys: self.map.keys(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Look up elements in batch mode, returning an iterator over
+    /// key-value pairs.
+    ///
+    /// `count` specifies the batch size.
+    pub fn lookup_batch(
+        &self,
+        count: u32,
+        elem_flags: MapFlags,
+        flags: MapFlags,
+    ) -> Result<TypedBatchIter<'map, K, V>> {
+        let iter = self.map.lookup_batch(count, elem_flags, flags)?;
+        Ok(TypedBatchIter {
+            iter,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Look up and delete elements in batch mode, returning an iterator
+    /// over the removed key-value pairs.
+    ///
+    /// `count` specifies the batch size.
+    pub fn lookup_and_delete_batch(
+        &self,
+        count: u32,
+        elem_flags: MapFlags,
+        flags: MapFlags,
+    ) -> Result<TypedBatchIter<'map, K, V>> {
+        let iter = self.map.lookup_and_delete_batch(count, elem_flags, flags)?;
+        Ok(TypedBatchIter {
+            iter,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Insert or update many elements in batch mode.
+    ///
+    /// `keys` and `values` must have the same number of elements.
+    pub fn update_batch(
+        &self,
+        keys: &[K],
+        values: &[V],
+        elem_flags: MapFlags,
+        flags: MapFlags,
+    ) -> Result<()> {
+        if keys.len() != values.len() {
+            return Err(Error::with_invalid_data(format!(
+                "number of keys {} != number of values {}",
+                keys.len(),
+                values.len()
+            )));
+        }
+
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        self.map.update_batch(
+            util::slice_as_bytes(keys),
+            util::slice_as_bytes(values),
+            keys.len() as u32,
+            elem_flags,
+            flags,
+        )
+    }
+
+    /// Delete many elements in batch mode.
+    pub fn delete_batch(&self, keys: &[K], elem_flags: MapFlags, flags: MapFlags) -> Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        self.map.delete_batch(
+            util::slice_as_bytes(keys),
+            keys.len() as u32,
+            elem_flags,
+            flags,
+        )
+    }
+}
+
+impl<K, V> Debug for TypedMap<'_, K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map, _phantom } = self;
+        f.debug_struct("TypedMap").field("map", map).finish()
+    }
+}
+
+/// An iterator over the keys of a [`TypedMap`].
+pub struct TypedKeyIter<'map, K> {
+    iter: MapKeyIter<'map>,
+    _phantom: PhantomData<fn() -> K>,
+}
+
+impl<K> Iterator for TypedKeyIter<'_, K>
+where
+    K: NoPadding,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|key| util::from_bytes(&key))
+    }
+}
+
+impl<K> Debug for TypedKeyIter<'_, K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { iter, _phantom } = self;
+        f.debug_struct("TypedKeyIter").field("iter", iter).finish()
+    }
+}
+
+/// An iterator over the key-value pairs of a [`TypedMap`].
+pub struct TypedMapIter<'map, K, V> {
+    map: &'map dyn MapCore,
+    keys: MapKeyIter<'map>,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> Iterator for TypedMapIter<'_, K, V>
+where
+    K: NoPadding,
+    V: NoPadding,
+{
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let key = self.keys.next()?;
+            match self.map.lookup(&key, MapFlags::ANY) {
+                Ok(Some(value)) => {
+                    return Some(Ok((util::from_bytes(&key), util::from_bytes(&value))))
+                }
+                // The key got removed in the meantime.
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl<K, V> Debug for TypedMapIter<'_, K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            map,
+            keys,
+            _phantom,
+        } = self;
+        f.debug_struct("TypedMapIter")
+            .field("map", map)
+            .field("keys", keys)
+            .finish()
+    }
+}
+
+/// An iterator over batches of key-value pairs of a [`TypedMap`].
+pub struct TypedBatchIter<'map, K, V> {
+    iter: BatchedMapIter<'map>,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> Iterator for TypedBatchIter<'_, K, V>
+where
+    K: NoPadding,
+    V: NoPadding,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(key, value)| (util::from_bytes(&key), util::from_bytes(&value)))
+    }
+}
+
+impl<K, V> Debug for TypedBatchIter<'_, K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { iter, _phantom } = self;
+        f.debug_struct("TypedBatchIter")
+            .field("iter", iter)
+            .finish()
+    }
+}
@@ -1,18 +1,36 @@
 use libc::E2BIG;
 use libc::ENOSPC;
 use std::io;
+use std::marker::PhantomData;
+use std::mem::align_of;
+use std::mem::size_of;
+use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::os::fd::AsRawFd;
+#[cfg(feature = "tokio")]
+use std::os::fd::RawFd;
+use std::os::raw::c_int;
 use std::os::raw::c_uint;
 use std::os::raw::c_void;
 use std::ptr::null_mut;
 use std::ptr::NonNull;
 use std::slice::from_raw_parts;
 use std::slice::from_raw_parts_mut;
+#[cfg(feature = "tokio")]
+use std::sync::Mutex;
+use std::time::Duration;
+
+use plain::Plain;
+#[cfg(feature = "tokio")]
+use tokio::io::unix::AsyncFd;
+#[cfg(feature = "tokio")]
+use tokio::io::Interest;
 
 use crate::AsRawLibbpf;
 use crate::Error;
+#[cfg(feature = "tokio")]
+use crate::ErrorExt as _;
 use crate::MapCore;
 use crate::MapType;
 use crate::Result;
@@ -65,6 +83,77 @@ impl Drop for UserRingBufferSample<'_> {
     }
 }
 
+/// A typed sample reserved in a [`UserRingBuffer`].
+///
+/// The sample provides access to a possibly uninitialized `T`. Convert
+/// it into an [`UserRingBufferSample`] to submit or discard it.
+#[derive(Debug)]
+pub struct UserRingBufferTypedSample<'slf, T> {
+    sample: UserRingBufferSample<'slf>,
+    _phantom: PhantomData<T>,
+}
+
+impl<'slf, T> UserRingBufferTypedSample<'slf, T>
+where
+    T: Plain,
+{
+    /// Retrieve a mutable reference to the sample's, possibly
+    /// uninitialized, value.
+    pub fn as_uninit_mut(&mut self) -> &mut MaybeUninit<T> {
+        // SAFETY: The sample is large enough to hold a `T` and suitably
+        //         aligned, as checked when it was reserved.
+        unsafe { &mut *self.sample.ptr.as_ptr().cast::<MaybeUninit<T>>() }
+    }
+
+    /// Initialize the sample with `value`, returning a mutable reference
+    /// to it.
+    pub fn write(&mut self, value: T) -> &mut T {
+        self.as_uninit_mut().write(value)
+    }
+
+    /// Convert the typed sample into an untyped one.
+    #[inline]
+    pub fn into_sample(self) -> UserRingBufferSample<'slf> {
+        self.sample
+    }
+}
+
+impl<'slf, T> From<UserRingBufferTypedSample<'slf, T>> for UserRingBufferSample<'slf> {
+    fn from(sample: UserRingBufferTypedSample<'slf, T>) -> Self {
+        sample.sample
+    }
+}
+
+/// Convert the `errno` of a failed reserve operation into an error.
+fn reserve_error(errno: io::Error) -> Error {
+    match errno.raw_os_error() {
+        Some(E2BIG) => Error::with_invalid_data("requested size is too large"),
+        Some(ENOSPC) => Error::with_invalid_data("not enough space in the ring buffer"),
+        _ => Error::from(errno),
+    }
+}
+
+/// Convert the result of a libbpf reserve operation into a sample
+/// pointer.
+fn check_reserved(sample_ptr: *mut c_void) -> Result<NonNull<c_void>> {
+    NonNull::new(sample_ptr).ok_or_else(|| {
+        // Fetch the current value of errno to determine the type of error.
+        reserve_error(io::Error::last_os_error())
+    })
+}
+
+/// Check that samples are suitable to hold a value of type `T`.
+fn check_typed<T>() -> Result<()> {
+    // Samples are always 8-byte aligned.
+    if align_of::<T>() > 8 {
+        return Err(Error::with_invalid_data(format!(
+            "alignment {} of sample type exceeds 8",
+            align_of::<T>()
+        )));
+    }
+    Ok(())
+}
+
 /// Represents a user ring buffer. This is a special kind of map that is used to
 /// transfer data between user space and kernel space.
 #[derive(Debug)]
@@ -110,16 +199,44 @@ impl UserRingBuffer {
     pub fn reserve(&self, size: usize) -> Result<UserRingBufferSample<'_>> {
         let sample_ptr =
             unsafe { libbpf_sys::user_ring_buffer__reserve(self.ptr.as_ptr(), size as c_uint) };
+        let ptr = check_reserved(sample_ptr)?;
 
-        let ptr = NonNull::new(sample_ptr).ok_or_else(|| {
-            // Fetch the current value of errno to determine the type of error.
-            let errno = io::Error::last_os_error();
-            match errno.raw_os_error() {
-                Some(E2BIG) => Error::with_invalid_data("requested size is too large"),
-                Some(ENOSPC) => Error::with_invalid_data("not enough space in the ring buffer"),
-                _ => Error::from(errno),
-            }
-        })?;
+        Ok(UserRingBufferSample {
+            ptr,
+            size,
+            submitted: false,
+            rb: self,
+        })
+    }
+
+    /// Reserve a sample in the user ring buffer, waiting for space to
+    /// become available for up to `timeout`.
+    ///
+    /// If `timeout` is `Duration::MAX`, this will block indefinitely
+    /// until space is available. An error of kind
+    /// [`ErrorKind::TimedOut`][crate::ErrorKind::TimedOut] is returned
+    /// if no space became available in time.
+    ///
+    /// Like [`UserRingBuffer::reserve`], this function is *not*
+    /// thread-safe.
+    pub fn reserve_blocking(
+        &self,
+        size: usize,
+        timeout: Duration,
+    ) -> Result<UserRingBufferSample<'_>> {
+        let mut timeout_ms = -1;
+        if timeout != Duration::MAX {
+            timeout_ms = timeout.as_millis() as c_int;
+        }
+
+        let sample_ptr = unsafe {
+            libbpf_sys::user_ring_buffer__reserve_blocking(
+                self.ptr.as_ptr(),
+                size as c_uint,
+                timeout_ms,
+            )
+        };
+        let ptr = check_reserved(sample_ptr)?;
 
         Ok(UserRingBufferSample {
             ptr,
@@ -129,6 +246,23 @@ impl UserRingBuffer {
         })
     }
 
+    /// Reserve a sample holding a value of type `T` in the user ring
+    /// buffer.
+    ///
+    /// Like [`UserRingBuffer::reserve`], this function is *not*
+    /// thread-safe.
+    pub fn reserve_typed<T>(&self) -> Result<UserRingBufferTypedSample<'_, T>>
+    where
+        T: Plain,
+    {
+        let () = check_typed::<T>()?;
+        let sample = self.reserve(size_of::<T>())?;
+        Ok(UserRingBufferTypedSample {
+            sample,
+            _phantom: PhantomData,
+        })
+    }
+
     /// Submit a sample to the user ring buffer.
     ///
     /// This function takes ownership of the sample and submits it to the ring
@@ -149,6 +283,19 @@ impl UserRingBuffer {
         // validation while maintaining backwards compatibility.
         Ok(())
     }
+
+    /// Discard a sample, releasing the space it occupies without making
+    /// it visible to the consumer.
+    ///
+    /// Dropping a sample without submitting it has the same effect.
+    pub fn discard(&self, mut sample: UserRingBufferSample<'_>) {
+        unsafe {
+            libbpf_sys::user_ring_buffer__discard(self.ptr.as_ptr(), sample.ptr.as_ptr());
+        }
+
+        // Prevent the sample from being discarded a second time on drop.
+        sample.submitted = true;
+    }
 }
 
 impl AsRawLibbpf for UserRingBuffer {
@@ -167,3 +314,125 @@ impl Drop for UserRingBuffer {
         }
     }
 }
+
+// SAFETY: `user_ring_buffer` objects are not tied to the thread that
+//         created them.
+unsafe impl Send for UserRingBuffer {}
+
+/// A [`UserRingBuffer`] that can be reserved from asynchronously.
+///
+/// An `AsyncUserRingBuffer` registers the user ring buffer map's file
+/// descriptor with the `tokio` reactor, so that tasks can wait for
+/// space to become available without blocking a thread. It is
+/// available with the `tokio` feature.
+///
+/// Unlike [`UserRingBuffer`], an `AsyncUserRingBuffer` can be shared
+/// between tasks running on different threads: reservations are
+/// serialized internally.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncUserRingBuffer<'map> {
+    fd: AsyncFd<RawFd>,
+    rb: UserRingBuffer,
+    // Serializes calls to `user_ring_buffer__reserve`, which is not
+    // thread-safe.
+    reserve_lock: Mutex<()>,
+    _map: PhantomData<&'map dyn MapCore>,
+}
+
+#[cfg(feature = "tokio")]
+impl<'map> AsyncUserRingBuffer<'map> {
+    /// Create a new `AsyncUserRingBuffer` from a map.
+    ///
+    /// This function has to be called from within the context of a
+    /// `tokio` runtime with IO enabled.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let rb = UserRingBuffer::new(map)?;
+        let fd = AsyncFd::with_interest(map.as_fd().as_raw_fd(), Interest::WRITABLE)
+            .context("failed to register user ring buffer with tokio reactor")?;
+        Ok(Self {
+            fd,
+            rb,
+            reserve_lock: Mutex::new(()),
+            _map: PhantomData,
+        })
+    }
+
+    /// Reserve a sample in the user ring buffer, waiting for space to
+    /// become available.
+    ///
+    /// This method is cancellation safe: if the returned future is
+    /// dropped before completion, no space will have been reserved.
+    pub async fn reserve(&self, size: usize) -> Result<UserRingBufferSample<'_>> {
+        loop {
+            let mut guard = self.fd.writable().await?;
+            let (sample_ptr, errno) = {
+                // Nothing can panic while the lock is held, so there is
+                // no state to worry about should it be poisoned.
+                let _lock = self
+                    .reserve_lock
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner());
+                let sample_ptr = unsafe {
+                    libbpf_sys::user_ring_buffer__reserve(self.rb.ptr.as_ptr(), size as c_uint)
+                };
+                (sample_ptr, io::Error::last_os_error())
+            };
+            match NonNull::new(sample_ptr) {
+                Some(ptr) => {
+                    break Ok(UserRingBufferSample {
+                        ptr,
+                        size,
+                        submitted: false,
+                        rb: &self.rb,
+                    })
+                }
+                None => {
+                    if errno.raw_os_error() != Some(ENOSPC) {
+                        break Err(reserve_error(errno))
+                    }
+                    // Wait for the consumer to free up space.
+                    let () = guard.clear_ready();
+                }
+            }
+        }
+    }
+
+    /// Reserve a sample holding a value of type `T` in the user ring
+    /// buffer, waiting for space to become available.
+    pub async fn reserve_typed<T>(&self) -> Result<UserRingBufferTypedSample<'_, T>>
+    where
+        T: Plain,
+    {
+        let () = check_typed::<T>()?;
+        let sample = self.reserve(size_of::<T>()).await?;
+        Ok(UserRingBufferTypedSample {
+            sample,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Submit a sample to the user ring buffer.
+    ///
+    /// See [`UserRingBuffer::submit`].
+    pub fn submit(&self, sample: UserRingBufferSample<'_>) -> Result<()> {
+        self.rb.submit(sample)
+    }
+
+    /// Discard a sample.
+    ///
+    /// See [`UserRingBuffer::discard`].
+    pub fn discard(&self, sample: UserRingBufferSample<'_>) {
+        self.rb.discard(sample)
+    }
+}
+
+// SAFETY: The only non-thread-safe operation on the underlying
+//         `user_ring_buffer`, reservation, is serialized by
+//         `reserve_lock`. Submitting and discarding samples is
+//         thread-safe. The map is borrowed only to keep its file
+//         descriptor alive and is never accessed.
+#[cfg(feature = "tokio")]
+unsafe impl Send for AsyncUserRingBuffer<'_> {}
+#[cfg(feature = "tokio")]
+unsafe impl Sync for AsyncUserRingBuffer<'_> {}
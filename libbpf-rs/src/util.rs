@@ -1,17 +1,24 @@
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::fs;
+use std::mem::size_of;
+use std::mem::size_of_val;
 use std::mem::transmute;
+use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::os::fd::AsRawFd;
 use std::os::fd::BorrowedFd;
 use std::os::raw::c_char;
 use std::path::Path;
+use std::ptr;
 use std::ptr::NonNull;
+use std::slice;
 use std::sync::OnceLock;
 
 use crate::error::IntoError;
 use crate::Error;
+use crate::MapCore;
+use crate::MapType;
 use crate::Result;
 
 pub fn str_to_cstring(s: &str) -> Result<CString> {
@@ -54,6 +61,119 @@ pub fn c_char_slice_to_cstr(s: &[c_char]) -> Option<&CStr> {
     Some(cstr)
 }
 
+/// A marker trait for plain data types that do not contain any padding
+/// bytes.
+///
+/// Typed map wrappers, such as [`TypedMap`][crate::TypedMap], pass
+/// keys and values to the kernel as their raw in-memory representation
+/// and reconstruct them from the bytes reported back. The contents of
+/// padding bytes are unspecified and reading them is undefined
+/// behavior, which is why these wrappers require this trait to be
+/// implemented for keys and values.
+///
+/// The trait is implemented for primitive integer types and arrays
+/// thereof. For other types, e.g., those generated by `libbpf-cargo`,
+/// users have to implement it themselves, after making sure that the
+/// type satisfies the requirements below. `libbpf-cargo` makes padding
+/// explicit using `__pad_*` fields, which helps with that.
+///
+/// # Safety
+/// Implementors have to be valid for any bit pattern, just as
+/// [`Plain`][plain::Plain] types, and must not contain any padding
+/// bytes, neither between fields nor at the end of the type.
+pub unsafe trait NoPadding {}
+
+macro_rules! impl_no_padding {
+    ($($ty:ty),*) => {
+        $(
+            // SAFETY: Primitive integer types do not contain padding
+            //         and any bit pattern is valid for them.
+            unsafe impl NoPadding for $ty {}
+        )*
+    };
+}
+
+impl_no_padding!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+// SAFETY: Array elements are laid out without any space between them
+//         and the element type does not contain padding itself.
+unsafe impl<T, const N: usize> NoPadding for [T; N] where T: NoPadding {}
+
+/// View a [`NoPadding`] object as its raw bytes.
+pub fn as_bytes<T: NoPadding>(value: &T) -> &[u8] {
+    // SAFETY: `value` is a valid reference and the slice covers exactly
+    //         the memory it refers to. `NoPadding` types do not contain
+    //         padding bytes or data with validity invariants, so all
+    //         bytes are initialized.
+    unsafe { slice::from_raw_parts((value as *const T).cast::<u8>(), size_of::<T>()) }
+}
+
+/// View a slice of [`NoPadding`] objects as its raw bytes.
+pub fn slice_as_bytes<T: NoPadding>(values: &[T]) -> &[u8] {
+    // SAFETY: `values` is a valid slice and the byte slice covers
+    //         exactly the memory it refers to. Neither the elements nor
+    //         the space between them contain padding bytes.
+    unsafe { slice::from_raw_parts(values.as_ptr().cast::<u8>(), size_of_val(values)) }
+}
+
+/// Reinterpret a potentially uninitialized object as a slice of
+/// potentially uninitialized bytes.
+pub fn uninit_as_bytes_mut<T>(value: &mut MaybeUninit<T>) -> &mut [MaybeUninit<u8>] {
+    // SAFETY: `MaybeUninit<u8>` does not have any validity requirements
+    //         and the slice covers exactly the object's memory.
+    unsafe { slice::from_raw_parts_mut(value.as_mut_ptr().cast(), size_of::<T>()) }
+}
+
+/// Read a [`NoPadding`] object from the start of the provided byte
+/// slice.
+///
+/// # Panics
+/// This function panics if `bytes` is shorter than `T`.
+pub fn from_bytes<T: NoPadding>(bytes: &[u8]) -> T {
+    assert!(
+        bytes.len() >= size_of::<T>(),
+        "buffer of {} bytes is too small for object of {} bytes",
+        bytes.len(),
+        size_of::<T>()
+    );
+    // SAFETY: The buffer is large enough and any bit pattern is a
+    //         valid `NoPadding` object. We do not make any assumptions
+    //         about the alignment of `bytes`.
+    unsafe { ptr::read_unaligned(bytes.as_ptr().cast::<T>()) }
+}
+
+/// Check that `map` is of one of the given types with keys of
+/// `key_size` bytes and values of one of the sizes in `value_sizes`.
+pub fn check_fd_map(
+    map: &dyn MapCore,
+    types: &[MapType],
+    key_size: usize,
+    value_sizes: &[usize],
+    what: &str,
+) -> Result<()> {
+    let map_type = map.map_type();
+    if !types.contains(&map_type) {
+        return Err(Error::with_invalid_data(format!(
+            "map type {map_type:?} is not supported by {what}"
+        )));
+    }
+
+    if map.key_size() as usize != key_size {
+        return Err(Error::with_invalid_data(format!(
+            "key_size {} != {key_size}",
+            map.key_size()
+        )));
+    }
+
+    if !value_sizes.contains(&(map.value_size() as usize)) {
+        return Err(Error::with_invalid_data(format!(
+            "value_size {} is not one of {value_sizes:?}",
+            map.value_size()
+        )));
+    }
+    Ok(())
+}
+
 /// Round up a number to the next multiple of `r`
 pub fn roundup(num: usize, r: usize) -> usize {
     ((num + (r - 1)) / r) * r
@@ -65,6 +185,29 @@ pub fn num_possible_cpus() -> Result<usize> {
     parse_ret(ret).map(|()| ret as usize)
 }
 
+/// Parse a CPU list as used by the kernel, e.g., `0-3,5,7-8`.
+fn parse_cpu_list(list: &str) -> Result<Vec<usize>> {
+    let parse = |cpu: &str| {
+        cpu.parse::<usize>()
+            .map_err(|_err| Error::with_invalid_data(format!("invalid CPU `{cpu}` in CPU list")))
+    };
+
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => cpus.extend(parse(start)?..=parse(end)?),
+            None => cpus.push(parse(range)?),
+        }
+    }
+    Ok(cpus)
+}
+
+/// Get the IDs of all CPUs that are currently online.
+pub fn online_cpus() -> Result<Vec<usize>> {
+    let list = fs::read_to_string("/sys/devices/system/cpu/online")?;
+    parse_cpu_list(&list)
+}
+
 pub fn parse_ret(ret: i32) -> Result<()> {
     if ret < 0 {
         // Error code is returned negative, flip to positive to match errno
@@ -187,6 +330,18 @@ mod tests {
         assert!(num > 0);
     }
 
+    /// Check that we can parse kernel CPU lists.
+    #[test]
+    fn cpu_list_parsing() {
+        assert_eq!(parse_cpu_list("").unwrap(), Vec::<usize>::new());
+        assert_eq!(parse_cpu_list("0\n").unwrap(), vec![0]);
+        assert_eq!(
+            parse_cpu_list("0-3,5,7-8").unwrap(),
+            vec![0, 1, 2, 3, 5, 7, 8]
+        );
+        assert!(parse_cpu_list("0-x").is_err());
+    }
+
     /// Check that we can convert a `[c_char]` into a `CStr`.
     #[test]
     fn c_char_slice_conversion() {
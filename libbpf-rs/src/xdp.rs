@@ -30,6 +30,106 @@ bitflags! {
 
 }
 
+// from kernel @ include/uapi/linux/if_link.h
+const XDP_ATTACHED_NONE: u8 = 0;
+const XDP_ATTACHED_DRV: u8 = 1;
+const XDP_ATTACHED_SKB: u8 = 2;
+const XDP_ATTACHED_HW: u8 = 3;
+const XDP_ATTACHED_MULTI: u8 = 4;
+
+bitflags! {
+    /// The XDP features supported by a network device, as reported by
+    /// its driver.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct XdpFeatures: u64 {
+        /// The driver supports native XDP, i.e., the `XDP_PASS`,
+        /// `XDP_DROP`, `XDP_ABORTED`, and `XDP_TX` actions in driver
+        /// mode.
+        const BASIC        = 1 << 0;
+        /// The driver supports the `XDP_REDIRECT` action.
+        const REDIRECT     = 1 << 1;
+        /// The device can be the target of an `XDP_REDIRECT`.
+        const NDO_XMIT     = 1 << 2;
+        /// The driver supports AF_XDP sockets in zero-copy mode.
+        const XSK_ZEROCOPY = 1 << 3;
+        /// The device supports offloading XDP programs to hardware.
+        const HW_OFFLOAD   = 1 << 4;
+        /// The driver supports non-linear (multi-buffer) packets in
+        /// driver mode.
+        const RX_SG        = 1 << 5;
+        /// The device can be the target of an `XDP_REDIRECT` of
+        /// non-linear packets.
+        const NDO_XMIT_SG  = 1 << 6;
+    }
+}
+
+/// The mode(s) XDP programs are attached to a network device in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XdpAttachMode {
+    /// No program is attached.
+    None,
+    /// A program is attached in native (driver) mode.
+    Drv,
+    /// A program is attached in generic (SKB) mode.
+    Skb,
+    /// A program is offloaded to hardware.
+    Hw,
+    /// Programs are attached in multiple modes, see the per-mode
+    /// program IDs of [`XdpInfo`].
+    Multi,
+    /// An attach mode unknown to this library.
+    Unknown(u8),
+}
+
+impl From<u8> for XdpAttachMode {
+    fn from(value: u8) -> Self {
+        match value {
+            XDP_ATTACHED_NONE => Self::None,
+            XDP_ATTACHED_DRV => Self::Drv,
+            XDP_ATTACHED_SKB => Self::Skb,
+            XDP_ATTACHED_HW => Self::Hw,
+            XDP_ATTACHED_MULTI => Self::Multi,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+/// The XDP state of a network device, as reported by [`Xdp::query`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XdpInfo {
+    /// The ID of the attached program, if a single one is attached.
+    pub prog_id: Option<u32>,
+    /// The ID of the program attached in native (driver) mode, if any.
+    pub drv_prog_id: Option<u32>,
+    /// The ID of the program attached in generic (SKB) mode, if any.
+    pub skb_prog_id: Option<u32>,
+    /// The ID of the program offloaded to hardware, if any.
+    pub hw_prog_id: Option<u32>,
+    /// The mode the program(s) are attached in.
+    pub attach_mode: XdpAttachMode,
+    /// The XDP features supported by the device. Empty on kernels that
+    /// do not report XDP features (before 6.3).
+    pub feature_flags: XdpFeatures,
+    /// The maximum number of fragments of a packet the device supports
+    /// with AF_XDP sockets in zero-copy mode. Zero if not reported.
+    pub xdp_zc_max_segs: u32,
+}
+
+impl From<libbpf_sys::bpf_xdp_query_opts> for XdpInfo {
+    fn from(opts: libbpf_sys::bpf_xdp_query_opts) -> Self {
+        let id = |id: u32| (id != 0).then_some(id);
+        Self {
+            prog_id: id(opts.prog_id),
+            drv_prog_id: id(opts.drv_prog_id),
+            skb_prog_id: id(opts.skb_prog_id),
+            hw_prog_id: id(opts.hw_prog_id),
+            attach_mode: XdpAttachMode::from(opts.attach_mode),
+            feature_flags: XdpFeatures::from_bits_retain(opts.feature_flags),
+            xdp_zc_max_segs: opts.xdp_zc_max_segs,
+        }
+    }
+}
+
 /// Represents a XDP program.
 ///
 /// This struct exposes operations to attach, detach and query a XDP program
@@ -78,11 +178,12 @@ impl<'fd> Xdp<'fd> {
         util::parse_ret(ret)
     }
 
-    /// Query to inspect the program
-    pub fn query(&self, ifindex: i32, flags: XdpFlags) -> Result<libbpf_sys::bpf_xdp_query_opts> {
+    /// Query the XDP state of the given interface, i.e., the programs
+    /// attached to it and the XDP features it supports.
+    pub fn query(&self, ifindex: i32, flags: XdpFlags) -> Result<XdpInfo> {
         let mut opts = self.query_opts;
         let err = unsafe { libbpf_sys::bpf_xdp_query(ifindex, flags.bits() as i32, &mut opts) };
-        util::parse_ret(err).map(|()| opts)
+        util::parse_ret(err).map(|()| XdpInfo::from(opts))
     }
 
     /// Query to inspect the program identifier (prog_id)
@@ -0,0 +1,283 @@
+use std::ffi::CString;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io;
+use std::mem::size_of;
+use std::os::unix::io::AsFd;
+use std::os::unix::io::AsRawFd as _;
+use std::os::unix::io::BorrowedFd;
+
+use crate::util;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::MapCore;
+use crate::MapFlags;
+use crate::MapType;
+use crate::Result;
+
+/// Retrieve the index of the network interface with the given name.
+pub(crate) fn ifindex_from_name(name: &str) -> Result<u32> {
+    let cname = CString::new(name).map_err(|_err| {
+        Error::with_invalid_data(format!("interface name `{name}` contains a NUL byte"))
+    })?;
+    let ifindex = unsafe { libc::if_nametoindex(cname.as_ptr()) };
+    if ifindex == 0 {
+        return Err(Error::from(io::Error::last_os_error()))
+            .with_context(|| format!("failed to find interface `{name}`"));
+    }
+    Ok(ifindex)
+}
+
+/// Update the entry `key` of a devmap or cpumap with a value consisting
+/// of `first` (ifindex or queue size) and an optional program.
+///
+/// Both `struct bpf_devmap_val` and `struct bpf_cpumap_val` are laid out
+/// this way.
+fn update_with_prog(
+    map: &dyn MapCore,
+    key: u32,
+    first: u32,
+    prog: Option<BorrowedFd<'_>>,
+    flags: MapFlags,
+) -> Result<()> {
+    let key = key.to_ne_bytes();
+    if map.value_size() as usize == size_of::<u32>() {
+        if prog.is_some() {
+            return Err(Error::with_invalid_data(
+                "programs can only be attached to entries of maps with 8 byte values",
+            ));
+        }
+        return map.update(&key, &first.to_ne_bytes(), flags)
+    }
+
+    // A program file descriptor of zero or less means no program.
+    let prog_fd = prog.map(|prog| prog.as_raw_fd()).unwrap_or(0);
+    let mut value = [0; 8];
+    let () = value[..4].copy_from_slice(&first.to_ne_bytes());
+    let () = value[4..].copy_from_slice(&prog_fd.to_ne_bytes());
+    map.update(&key, &value, flags)
+}
+
+/// Look up the entry `key` of a devmap or cpumap, returning the first
+/// value member and the ID of the attached program, if any.
+fn lookup_with_prog(map: &dyn MapCore, key: u32) -> Result<Option<(u32, Option<u32>)>> {
+    let mut value = [0; 8];
+    let value = &mut value[..map.value_size() as usize];
+    let found = map.lookup_into(&key.to_ne_bytes(), value, MapFlags::ANY)?;
+    if !found {
+        return Ok(None)
+    }
+
+    let first = u32::from_ne_bytes(value[..4].try_into().unwrap());
+    // Maps with four byte values don't have a program ID.
+    let prog_id = value
+        .get(4..8)
+        .map(|id| u32::from_ne_bytes(id.try_into().unwrap()))
+        .filter(|id| *id != 0);
+    Ok(Some((first, prog_id)))
+}
+
+/// An entry of a [`DevMap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DevMapValue {
+    /// The index of the network interface packets are redirected to.
+    pub ifindex: u32,
+    /// The ID of the XDP program run on redirected packets, if any.
+    pub prog_id: Option<u32>,
+}
+
+/// A typed view on a [`MapType::Devmap`] or [`MapType::DevmapHash`] map,
+/// used for redirecting packets to other network interfaces with
+/// `bpf_redirect_map()`.
+///
+/// Programs to run on redirected packets, before they are transmitted,
+/// have to be loaded from a `SEC("xdp/devmap")` section and require the
+/// map's value to be eight bytes large, i.e., a `struct
+/// bpf_devmap_val`.
+pub struct DevMap<'map> {
+    map: &'map dyn MapCore,
+}
+
+impl<'map> DevMap<'map> {
+    /// Create a new `DevMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::Devmap`] or
+    ///   [`MapType::DevmapHash`].
+    /// * If the map's key is not four bytes large or its value is
+    ///   neither four nor eight bytes large.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let () = util::check_fd_map(
+            map,
+            &[MapType::Devmap, MapType::DevmapHash],
+            size_of::<u32>(),
+            &[4, 8],
+            "DevMap",
+        )?;
+        Ok(Self { map })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Redirect packets for `key` to the interface with index `ifindex`,
+    /// optionally running `prog` on them first.
+    pub fn insert(
+        &self,
+        key: u32,
+        ifindex: u32,
+        prog: Option<BorrowedFd<'_>>,
+        flags: MapFlags,
+    ) -> Result<()> {
+        update_with_prog(self.map, key, ifindex, prog, flags)
+    }
+
+    /// Redirect packets for `key` to the interface with the given name,
+    /// optionally running `prog` on them first.
+    pub fn insert_by_name(
+        &self,
+        key: u32,
+        ifname: &str,
+        prog: Option<BorrowedFd<'_>>,
+        flags: MapFlags,
+    ) -> Result<()> {
+        let ifindex = ifindex_from_name(ifname)?;
+        self.insert(key, ifindex, prog, flags)
+    }
+
+    /// Look up the entry for `key`.
+    pub fn get(&self, key: u32) -> Result<Option<DevMapValue>> {
+        let value = lookup_with_prog(self.map, key)?;
+        Ok(value.map(|(ifindex, prog_id)| DevMapValue { ifindex, prog_id }))
+    }
+
+    /// Remove the entry for `key`.
+    pub fn remove(&self, key: u32) -> Result<()> {
+        self.map.delete(&key.to_ne_bytes())
+    }
+}
+
+impl Debug for DevMap<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map } = self;
+        f.debug_struct("DevMap").field("map", map).finish()
+    }
+}
+
+/// An entry of a [`CpuMap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuMapValue {
+    /// The size of the queue of packets redirected to the CPU.
+    pub qsize: u32,
+    /// The ID of the XDP program run on redirected packets, if any.
+    pub prog_id: Option<u32>,
+}
+
+/// A typed view on a [`MapType::Cpumap`] map, used for redirecting
+/// packets to other CPUs with `bpf_redirect_map()`.
+///
+/// Programs to run on redirected packets on the remote CPU have to be
+/// loaded from a `SEC("xdp/cpumap")` section and require the map's
+/// value to be eight bytes large, i.e., a `struct bpf_cpumap_val`.
+pub struct CpuMap<'map> {
+    map: &'map dyn MapCore,
+}
+
+impl<'map> CpuMap<'map> {
+    /// Create a new `CpuMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::Cpumap`].
+    /// * If the map's key is not four bytes large or its value is
+    ///   neither four nor eight bytes large.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let () = util::check_fd_map(map, &[MapType::Cpumap], size_of::<u32>(), &[4, 8], "CpuMap")?;
+        Ok(Self { map })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Enable redirection to `cpu` using a queue of `qsize` packets,
+    /// optionally running `prog` on them on the remote CPU.
+    pub fn insert(
+        &self,
+        cpu: u32,
+        qsize: u32,
+        prog: Option<BorrowedFd<'_>>,
+        flags: MapFlags,
+    ) -> Result<()> {
+        update_with_prog(self.map, cpu, qsize, prog, flags)
+    }
+
+    /// Look up the entry for `cpu`.
+    pub fn get(&self, cpu: u32) -> Result<Option<CpuMapValue>> {
+        let value = lookup_with_prog(self.map, cpu)?;
+        Ok(value.map(|(qsize, prog_id)| CpuMapValue { qsize, prog_id }))
+    }
+
+    /// Disable redirection to `cpu`.
+    pub fn remove(&self, cpu: u32) -> Result<()> {
+        self.map.delete(&cpu.to_ne_bytes())
+    }
+}
+
+impl Debug for CpuMap<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map } = self;
+        f.debug_struct("CpuMap").field("map", map).finish()
+    }
+}
+
+/// A typed view on a [`MapType::Xskmap`] map, used for redirecting
+/// packets to AF_XDP sockets with `bpf_redirect_map()`.
+///
+/// Entries are indexed by queue ID, by convention. Contrary to other
+/// maps, entries of an `XskMap` cannot be looked up from user space.
+pub struct XskMap<'map> {
+    map: &'map dyn MapCore,
+}
+
+impl<'map> XskMap<'map> {
+    /// Create a new `XskMap` on top of the provided map.
+    ///
+    /// # Errors
+    /// * If the map is not of type [`MapType::Xskmap`].
+    /// * If the map's key or value are not four bytes large.
+    pub fn new(map: &'map dyn MapCore) -> Result<Self> {
+        let () = util::check_fd_map(map, &[MapType::Xskmap], size_of::<u32>(), &[4], "XskMap")?;
+        Ok(Self { map })
+    }
+
+    /// Retrieve the underlying map.
+    #[inline]
+    pub fn map(&self) -> &'map dyn MapCore {
+        self.map
+    }
+
+    /// Redirect packets for `queue` to the given AF_XDP socket.
+    pub fn insert(&self, queue: u32, socket: impl AsFd, flags: MapFlags) -> Result<()> {
+        let fd = socket.as_fd().as_raw_fd() as u32;
+        self.map
+            .update(&queue.to_ne_bytes(), &fd.to_ne_bytes(), flags)
+    }
+
+    /// Remove the socket for `queue`.
+    pub fn remove(&self, queue: u32) -> Result<()> {
+        self.map.delete(&queue.to_ne_bytes())
+    }
+}
+
+impl Debug for XskMap<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self { map } = self;
+        f.debug_struct("XskMap").field("map", map).finish()
+    }
+}
@@ -0,0 +1,715 @@
+use std::io;
+use std::mem::align_of;
+use std::mem::size_of;
+use std::os::unix::io::AsFd;
+use std::os::unix::io::AsRawFd as _;
+use std::os::unix::io::BorrowedFd;
+use std::os::unix::io::FromRawFd as _;
+use std::os::unix::io::OwnedFd;
+use std::ptr;
+use std::ptr::NonNull;
+use std::slice;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use bitflags::bitflags;
+
+use crate::util;
+use crate::xdp_map::ifindex_from_name;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::MapFlags;
+use crate::Result;
+use crate::XskMap;
+
+// from kernel @ include/uapi/linux/if_xdp.h
+const XDP_MMAP_OFFSETS: libc::c_int = 1;
+const XDP_RX_RING: libc::c_int = 2;
+const XDP_TX_RING: libc::c_int = 3;
+const XDP_UMEM_REG: libc::c_int = 4;
+const XDP_UMEM_FILL_RING: libc::c_int = 5;
+const XDP_UMEM_COMPLETION_RING: libc::c_int = 6;
+const XDP_PGOFF_RX_RING: libc::off_t = 0;
+const XDP_PGOFF_TX_RING: libc::off_t = 0x80000000;
+const XDP_UMEM_PGOFF_FILL_RING: libc::off_t = 0x100000000;
+const XDP_UMEM_PGOFF_COMPLETION_RING: libc::off_t = 0x180000000;
+const XDP_RING_NEED_WAKEUP: u32 = 1 << 0;
+
+#[repr(C)]
+#[derive(Default)]
+struct XdpRingOffset {
+    producer: u64,
+    consumer: u64,
+    desc: u64,
+    flags: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct XdpMmapOffsets {
+    rx: XdpRingOffset,
+    tx: XdpRingOffset,
+    fr: XdpRingOffset,
+    cr: XdpRingOffset,
+}
+
+// The kernel accepts `struct xdp_umem_reg` in different sizes and
+// infers the layout from the option length: `tx_metadata_len` is only
+// known to Linux 6.8 and later. Unless TX metadata is requested, we
+// pass the option length of the preceding (v2) layout, which ends with
+// `flags`, so that older kernels accept it as well.
+#[repr(C)]
+struct XdpUmemReg {
+    addr: u64,
+    len: u64,
+    chunk_size: u32,
+    headroom: u32,
+    flags: u32,
+    tx_metadata_len: u32,
+}
+
+impl XdpUmemReg {
+    /// The size of the v2 layout, without `tx_metadata_len`.
+    fn v2_size() -> usize {
+        util::roundup(
+            2 * size_of::<u64>() + 3 * size_of::<u32>(),
+            align_of::<Self>(),
+        )
+    }
+}
+
+#[repr(C)]
+struct SockaddrXdp {
+    sxdp_family: u16,
+    sxdp_flags: u16,
+    sxdp_ifindex: u32,
+    sxdp_queue_id: u32,
+    sxdp_shared_umem_fd: u32,
+}
+
+bitflags! {
+    /// Flags to configure the binding of an [`XskSocket`].
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct XskBindFlags: u16 {
+        /// Let the kernel pick zero-copy mode if supported by the
+        /// driver, falling back to copy mode otherwise.
+        const NONE            = 0;
+        /// Force copy mode.
+        const COPY            = 1 << 1;
+        /// Force zero-copy mode, failing if not supported by the
+        /// driver.
+        const ZEROCOPY        = 1 << 2;
+        /// Only process the fill and TX rings when explicitly woken up,
+        /// as indicated by [`XskSocket::fill_needs_wakeup`] and
+        /// [`XskSocket::tx_needs_wakeup`].
+        const USE_NEED_WAKEUP = 1 << 3;
+    }
+}
+
+impl Default for XskBindFlags {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// A descriptor of a packet in the UMEM of an [`XskSocket`], as found
+/// on the RX and TX rings.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct XdpDesc {
+    /// The offset of the packet data in the UMEM.
+    pub addr: u64,
+    /// The length of the packet data.
+    pub len: u32,
+    /// Packet options.
+    pub options: u32,
+}
+
+/// Configuration of an [`XskSocket`].
+#[derive(Clone, Debug)]
+pub struct XskConfig {
+    /// The number of frames in the UMEM.
+    pub frame_count: u32,
+    /// The size of a single frame. Has to be a power of two between
+    /// 2048 and the page size.
+    pub frame_size: u32,
+    /// The headroom reserved at the start of each frame.
+    pub frame_headroom: u32,
+    /// The size of the TX metadata area in front of packets to transmit.
+    /// Zero disables TX metadata, which is only supported by Linux 6.8
+    /// and later.
+    pub tx_metadata_len: u32,
+    /// The number of entries of the fill ring. Has to be a power of
+    /// two.
+    pub fill_size: u32,
+    /// The number of entries of the completion ring. Has to be a power
+    /// of two.
+    pub comp_size: u32,
+    /// The number of entries of the RX ring. Has to be a power of two.
+    pub rx_size: u32,
+    /// The number of entries of the TX ring. Has to be a power of two.
+    pub tx_size: u32,
+    /// Flags to bind the socket with.
+    pub bind_flags: XskBindFlags,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for XskConfig {
+    fn default() -> Self {
+        Self {
+            frame_count: 4096,
+            frame_size: 4096,
+            frame_headroom: 0,
+            tx_metadata_len: 0,
+            fill_size: 2048,
+            comp_size: 2048,
+            rx_size: 2048,
+            tx_size: 2048,
+            bind_flags: XskBindFlags::NONE,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+/// Convert a negative return value of a libc call into an error.
+fn check_libc(ret: libc::c_int) -> Result<()> {
+    if ret < 0 {
+        return Err(Error::from(io::Error::last_os_error()))
+    }
+    Ok(())
+}
+
+/// Memory map `len` bytes, backed by `fd` at `offset` or anonymous
+/// memory if `fd` is `None`.
+fn mmap(fd: Option<BorrowedFd<'_>>, len: usize, offset: libc::off_t) -> Result<NonNull<u8>> {
+    let (fd, flags) = match fd {
+        Some(fd) => (fd.as_raw_fd(), libc::MAP_SHARED | libc::MAP_POPULATE),
+        None => (-1, libc::MAP_PRIVATE | libc::MAP_ANONYMOUS),
+    };
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            flags,
+            fd,
+            offset,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(Error::from(io::Error::last_os_error()))
+    }
+    // SAFETY: `mmap` never returns NULL on success.
+    Ok(unsafe { NonNull::new_unchecked(ptr.cast()) })
+}
+
+/// An anonymous memory mapping, unmapped on drop.
+#[derive(Debug)]
+struct Mmap {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        let _ret = unsafe { libc::munmap(self.ptr.as_ptr().cast(), self.len) };
+    }
+}
+
+/// Validate the UMEM and ring sizes of `config`, returning the size of
+/// the UMEM.
+fn check_config(config: &XskConfig) -> Result<usize> {
+    let XskConfig {
+        frame_count,
+        frame_size,
+        fill_size,
+        comp_size,
+        rx_size,
+        tx_size,
+        ..
+    } = *config;
+
+    if frame_count == 0 {
+        return Err(Error::with_invalid_input("frame count must not be zero"))
+    }
+    if frame_size == 0 {
+        return Err(Error::with_invalid_input("frame size must not be zero"))
+    }
+
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    if !frame_size.is_power_of_two() || !(2048..=page_size).contains(&(frame_size as usize)) {
+        return Err(Error::with_invalid_input(format!(
+            "frame size {frame_size} is not a power of two in 2048..={page_size}"
+        )))
+    }
+
+    let len = (frame_count as usize)
+        .checked_mul(frame_size as usize)
+        .ok_or_else(|| {
+            Error::with_invalid_input(format!(
+                "UMEM of {frame_count} frames of {frame_size} bytes is too large"
+            ))
+        })?;
+
+    for (size, what) in [
+        (fill_size, "fill"),
+        (comp_size, "completion"),
+        (rx_size, "RX"),
+        (tx_size, "TX"),
+    ] {
+        if !size.is_power_of_two() {
+            return Err(Error::with_invalid_input(format!(
+                "{what} ring size {size} is not a power of two"
+            )))
+        }
+    }
+    Ok(len)
+}
+
+/// A single producer/single consumer ring shared with the kernel.
+#[derive(Debug)]
+struct Ring<T> {
+    _mmap: Mmap,
+    producer: NonNull<AtomicU32>,
+    consumer: NonNull<AtomicU32>,
+    flags: NonNull<AtomicU32>,
+    descs: NonNull<T>,
+    size: u32,
+    cached_prod: u32,
+    cached_cons: u32,
+}
+
+impl<T> Ring<T>
+where
+    T: Copy,
+{
+    fn new(fd: BorrowedFd<'_>, off: &XdpRingOffset, size: u32, pgoff: libc::off_t) -> Result<Self> {
+        let len = off.desc as usize + size as usize * size_of::<T>();
+        let ptr = mmap(Some(fd), len, pgoff)?;
+        let _mmap = Mmap { ptr, len };
+        // SAFETY: The offsets reported by the kernel are located within
+        //         the mapping.
+        let at = |offset: u64| unsafe { NonNull::new_unchecked(ptr.as_ptr().add(offset as usize)) };
+        let producer = at(off.producer).cast::<AtomicU32>();
+        let consumer = at(off.consumer).cast::<AtomicU32>();
+
+        Ok(Self {
+            _mmap,
+            producer,
+            consumer,
+            flags: at(off.flags).cast(),
+            descs: at(off.desc).cast(),
+            size,
+            // SAFETY: The positions are four byte aligned words within
+            //         the mapping.
+            cached_prod: unsafe { producer.as_ref() }.load(Ordering::Relaxed),
+            cached_cons: unsafe { consumer.as_ref() }.load(Ordering::Relaxed),
+        })
+    }
+
+    #[inline]
+    fn producer(&self) -> &AtomicU32 {
+        unsafe { self.producer.as_ref() }
+    }
+
+    #[inline]
+    fn consumer(&self) -> &AtomicU32 {
+        unsafe { self.consumer.as_ref() }
+    }
+
+    #[inline]
+    fn desc(&self, idx: u32) -> *mut T {
+        // SAFETY: The index is masked to the ring size.
+        unsafe { self.descs.as_ptr().add((idx & (self.size - 1)) as usize) }
+    }
+
+    fn needs_wakeup(&self) -> bool {
+        let flags = unsafe { self.flags.as_ref() }.load(Ordering::Relaxed);
+        flags & XDP_RING_NEED_WAKEUP != 0
+    }
+
+    /// Produce as many of `items` as there is room for, returning the
+    /// number of produced items.
+    fn produce(&mut self, items: &[T]) -> usize {
+        let mut free = self.size - self.cached_prod.wrapping_sub(self.cached_cons);
+        if (free as usize) < items.len() {
+            self.cached_cons = self.consumer().load(Ordering::Acquire);
+            free = self.size - self.cached_prod.wrapping_sub(self.cached_cons);
+        }
+
+        let cnt = items.len().min(free as usize);
+        for (i, item) in items[..cnt].iter().enumerate() {
+            let () = unsafe {
+                self.desc(self.cached_prod.wrapping_add(i as u32))
+                    .write(*item)
+            };
+        }
+        self.cached_prod = self.cached_prod.wrapping_add(cnt as u32);
+        let () = self.producer().store(self.cached_prod, Ordering::Release);
+        cnt
+    }
+
+    /// Consume up to `out.len()` items, returning the number of consumed
+    /// items.
+    fn consume(&mut self, out: &mut [T]) -> usize {
+        let mut avail = self.cached_prod.wrapping_sub(self.cached_cons);
+        if (avail as usize) < out.len() {
+            self.cached_prod = self.producer().load(Ordering::Acquire);
+            avail = self.cached_prod.wrapping_sub(self.cached_cons);
+        }
+
+        let cnt = out.len().min(avail as usize);
+        for (i, item) in out[..cnt].iter_mut().enumerate() {
+            *item = unsafe { self.desc(self.cached_cons.wrapping_add(i as u32)).read() };
+        }
+        self.cached_cons = self.cached_cons.wrapping_add(cnt as u32);
+        let () = self.consumer().store(self.cached_cons, Ordering::Release);
+        cnt
+    }
+}
+
+// SAFETY: Each ring has a single producer and a single consumer, one of
+//         which is the kernel. Our side only ever produces or consumes
+//         through `&mut self` and synchronizes with the kernel through
+//         the atomic producer and consumer positions, so the ring can be
+//         moved to a different thread.
+unsafe impl<T> Send for Ring<T> where T: Send {}
+
+/// An [AF_XDP](https://docs.kernel.org/networking/af_xdp.html) socket,
+/// along with its UMEM and rings.
+///
+/// Packets are exchanged with the kernel through frames of the UMEM, a
+/// memory area shared with the kernel:
+/// * frames to receive packets into are handed to the kernel using
+///   [`XskSocket::fill`] and come back, filled with a packet, through
+///   [`XskSocket::receive`]
+/// * packets in frames passed to [`XskSocket::transmit`] are sent out,
+///   after which the frames come back through [`XskSocket::complete`]
+///
+/// Packets are only delivered to the socket if an XDP program redirects
+/// them into an [`XskMap`] that contains the socket, see
+/// [`XskSocket::register`].
+#[derive(Debug)]
+pub struct XskSocket {
+    // Note that the rings have to be unmapped before the socket is
+    // closed and the UMEM has to stay alive until the socket is closed.
+    fill: Ring<u64>,
+    comp: Ring<u64>,
+    rx: Ring<XdpDesc>,
+    tx: Ring<XdpDesc>,
+    fd: OwnedFd,
+    umem: Mmap,
+    frame_size: u32,
+    bind_flags: XskBindFlags,
+    ifindex: u32,
+    queue_id: u32,
+}
+
+impl XskSocket {
+    /// Create an AF_XDP socket with its own UMEM and bind it to the
+    /// given queue of the given network interface.
+    pub fn new(ifindex: u32, queue_id: u32, config: XskConfig) -> Result<Self> {
+        let len = check_config(&config)?;
+        let XskConfig {
+            frame_count: _,
+            frame_size,
+            frame_headroom,
+            tx_metadata_len,
+            fill_size,
+            comp_size,
+            rx_size,
+            tx_size,
+            bind_flags,
+            _non_exhaustive,
+        } = config;
+
+        let umem = Mmap {
+            ptr: mmap(None, len, 0).context("failed to allocate UMEM")?,
+            len,
+        };
+
+        let fd = unsafe { libc::socket(libc::AF_XDP, libc::SOCK_RAW | libc::SOCK_CLOEXEC, 0) };
+        let () = check_libc(fd).context("failed to create AF_XDP socket")?;
+        // SAFETY: `socket` returned a valid file descriptor, which we
+        //         now own.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let setsockopt = |opt, value: *const libc::c_void, len: usize| {
+            let ret =
+                unsafe { libc::setsockopt(fd.as_raw_fd(), libc::SOL_XDP, opt, value, len as _) };
+            check_libc(ret)
+        };
+        let reg = XdpUmemReg {
+            addr: umem.ptr.as_ptr() as u64,
+            len: len as u64,
+            chunk_size: frame_size,
+            headroom: frame_headroom,
+            flags: 0,
+            tx_metadata_len,
+        };
+        let reg_len = if tx_metadata_len != 0 {
+            size_of::<XdpUmemReg>()
+        } else {
+            XdpUmemReg::v2_size()
+        };
+        let () = setsockopt(XDP_UMEM_REG, &reg as *const _ as *const _, reg_len)
+            .context("failed to register UMEM")?;
+        for (opt, size) in [
+            (XDP_UMEM_FILL_RING, fill_size),
+            (XDP_UMEM_COMPLETION_RING, comp_size),
+            (XDP_RX_RING, rx_size),
+            (XDP_TX_RING, tx_size),
+        ] {
+            let () = setsockopt(opt, &size as *const _ as *const _, size_of::<u32>())
+                .context("failed to set AF_XDP ring size")?;
+        }
+
+        let mut off = XdpMmapOffsets::default();
+        let mut optlen = size_of::<XdpMmapOffsets>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_XDP,
+                XDP_MMAP_OFFSETS,
+                &mut off as *mut _ as *mut _,
+                &mut optlen,
+            )
+        };
+        let () = check_libc(ret).context("failed to retrieve AF_XDP ring offsets")?;
+
+        let fill = Ring::new(fd.as_fd(), &off.fr, fill_size, XDP_UMEM_PGOFF_FILL_RING)
+            .context("failed to mmap fill ring")?;
+        let comp = Ring::new(
+            fd.as_fd(),
+            &off.cr,
+            comp_size,
+            XDP_UMEM_PGOFF_COMPLETION_RING,
+        )
+        .context("failed to mmap completion ring")?;
+        let rx = Ring::new(fd.as_fd(), &off.rx, rx_size, XDP_PGOFF_RX_RING)
+            .context("failed to mmap RX ring")?;
+        let tx = Ring::new(fd.as_fd(), &off.tx, tx_size, XDP_PGOFF_TX_RING)
+            .context("failed to mmap TX ring")?;
+
+        let addr = SockaddrXdp {
+            sxdp_family: libc::AF_XDP as _,
+            sxdp_flags: bind_flags.bits(),
+            sxdp_ifindex: ifindex,
+            sxdp_queue_id: queue_id,
+            sxdp_shared_umem_fd: 0,
+        };
+        let ret = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const _ as *const libc::sockaddr,
+                size_of::<SockaddrXdp>() as _,
+            )
+        };
+        let () = check_libc(ret).context("failed to bind AF_XDP socket")?;
+
+        Ok(Self {
+            fill,
+            comp,
+            rx,
+            tx,
+            fd,
+            umem,
+            frame_size,
+            bind_flags,
+            ifindex,
+            queue_id,
+        })
+    }
+
+    /// Create an AF_XDP socket with its own UMEM and bind it to the
+    /// given queue of the network interface with the given name.
+    pub fn new_by_name(ifname: &str, queue_id: u32, config: XskConfig) -> Result<Self> {
+        let ifindex = ifindex_from_name(ifname)?;
+        Self::new(ifindex, queue_id, config)
+    }
+
+    /// Retrieve the index of the network interface the socket is bound
+    /// to.
+    #[inline]
+    pub fn ifindex(&self) -> u32 {
+        self.ifindex
+    }
+
+    /// Retrieve the queue the socket is bound to.
+    #[inline]
+    pub fn queue_id(&self) -> u32 {
+        self.queue_id
+    }
+
+    /// Retrieve the number of frames in the UMEM.
+    #[inline]
+    pub fn frame_count(&self) -> u32 {
+        (self.umem.len / self.frame_size as usize) as u32
+    }
+
+    /// Retrieve the UMEM address of the frame with the given index.
+    #[inline]
+    pub fn frame_addr(&self, idx: u32) -> u64 {
+        u64::from(idx) * u64::from(self.frame_size)
+    }
+
+    /// Retrieve the `len` bytes of packet data at UMEM address `addr`,
+    /// e.g., as described by an [`XdpDesc`].
+    ///
+    /// Returns `None` if the range is out of bounds of the UMEM.
+    ///
+    /// # Safety
+    /// The frames covered by the range have to be owned by user space,
+    /// i.e., they must not currently be on the fill or TX ring, for as
+    /// long as the returned slice exists. Otherwise the kernel may
+    /// modify them concurrently.
+    pub unsafe fn data(&self, addr: u64, len: usize) -> Option<&[u8]> {
+        let start = usize::try_from(addr).ok()?;
+        if start.checked_add(len)? > self.umem.len {
+            return None
+        }
+        // SAFETY: The range is within the UMEM and the caller
+        //         guarantees that the kernel does not access it.
+        Some(unsafe { slice::from_raw_parts(self.umem.ptr.as_ptr().add(start), len) })
+    }
+
+    /// Retrieve the `len` bytes of packet data at UMEM address `addr`
+    /// mutably, e.g., to write a packet before transmitting it.
+    ///
+    /// Returns `None` if the range is out of bounds of the UMEM.
+    ///
+    /// # Safety
+    /// The same ownership requirements as for [`XskSocket::data`]
+    /// apply.
+    pub unsafe fn data_mut(&mut self, addr: u64, len: usize) -> Option<&mut [u8]> {
+        let start = usize::try_from(addr).ok()?;
+        if start.checked_add(len)? > self.umem.len {
+            return None
+        }
+        // SAFETY: The range is within the UMEM and the caller
+        //         guarantees that the kernel does not access it.
+        Some(unsafe { slice::from_raw_parts_mut(self.umem.ptr.as_ptr().add(start), len) })
+    }
+
+    /// Hand the frames at the given UMEM addresses to the kernel for
+    /// receiving packets.
+    ///
+    /// Returns the number of frames that fit onto the fill ring.
+    pub fn fill(&mut self, addrs: &[u64]) -> usize {
+        self.fill.produce(addrs)
+    }
+
+    /// Retrieve the UMEM addresses of frames that have been
+    /// transmitted, returning their number.
+    pub fn complete(&mut self, addrs: &mut [u64]) -> usize {
+        self.comp.consume(addrs)
+    }
+
+    /// Retrieve descriptors of received packets, returning their
+    /// number.
+    ///
+    /// The frames of received packets are owned by user space until
+    /// handed back to the kernel using [`XskSocket::fill`] or
+    /// [`XskSocket::transmit`].
+    pub fn receive(&mut self, descs: &mut [XdpDesc]) -> usize {
+        self.rx.consume(descs)
+    }
+
+    /// Submit packets for transmission.
+    ///
+    /// Returns the number of packets that fit onto the TX ring.
+    /// Transmission may need to be triggered using
+    /// [`XskSocket::kick_tx`].
+    pub fn transmit(&mut self, descs: &[XdpDesc]) -> usize {
+        self.tx.produce(descs)
+    }
+
+    /// Check whether the kernel needs to be woken up, e.g., using
+    /// [`XskSocket::wait`], to process the fill ring.
+    ///
+    /// Always `true` if the socket has not been bound with
+    /// [`XskBindFlags::USE_NEED_WAKEUP`].
+    pub fn fill_needs_wakeup(&self) -> bool {
+        !self.bind_flags.contains(XskBindFlags::USE_NEED_WAKEUP) || self.fill.needs_wakeup()
+    }
+
+    /// Check whether the kernel needs to be woken up using
+    /// [`XskSocket::kick_tx`] to process the TX ring.
+    ///
+    /// Always `true` if the socket has not been bound with
+    /// [`XskBindFlags::USE_NEED_WAKEUP`].
+    pub fn tx_needs_wakeup(&self) -> bool {
+        !self.bind_flags.contains(XskBindFlags::USE_NEED_WAKEUP) || self.tx.needs_wakeup()
+    }
+
+    /// Trigger the transmission of packets on the TX ring.
+    pub fn kick_tx(&self) -> Result<()> {
+        let ret = unsafe {
+            libc::sendto(
+                self.fd.as_raw_fd(),
+                ptr::null(),
+                0,
+                libc::MSG_DONTWAIT,
+                ptr::null(),
+                0,
+            )
+        };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            // These errors signal that the kernel is still busy with
+            // earlier packets, which is not a problem.
+            match err.raw_os_error() {
+                Some(libc::ENOBUFS | libc::EAGAIN | libc::EBUSY | libc::ENETDOWN) => (),
+                _ => return Err(Error::from(err)).context("failed to kick AF_XDP TX ring"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait until packets are available on the RX ring or `timeout` is
+    /// reached. If `timeout` is Duration::MAX, this will block
+    /// indefinitely until packets are available.
+    ///
+    /// Return whether packets are available.
+    pub fn wait(&self, timeout: Duration) -> Result<bool> {
+        let mut timeout_ms = -1;
+        if timeout != Duration::MAX {
+            timeout_ms = timeout.as_millis() as i32;
+        }
+
+        let mut pollfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ret = unsafe { libc::poll(&mut pollfd, 1, timeout_ms) };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                return Ok(false)
+            }
+            return Err(Error::from(err))
+        }
+        Ok(ret > 0)
+    }
+
+    /// Register the socket in `map` for its queue, so that XDP programs
+    /// can redirect packets to it.
+    pub fn register(&self, map: &XskMap<'_>) -> Result<()> {
+        map.insert(self.queue_id, self, MapFlags::ANY)
+            .context("failed to register AF_XDP socket in XSKMAP")
+    }
+}
+
+impl AsFd for XskSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+// SAFETY: The UMEM and the rings are owned by the socket and are not
+//         tied to the thread that created them. Ring operations require
+//         `&mut self`, which guarantees exclusive access on our side.
+unsafe impl Send for XskSocket {}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+SEC("cgroup_skb/ingress")
+int handle__cgroup_skb(struct __sk_buff *skb) {
+  return 1;
+}
+
+SEC("cgroup_skb/ingress")
+int handle__cgroup_skb2(struct __sk_buff *skb) {
+  return 1;
+}
+
+char LICENSE[] SEC("license") = "GPL";
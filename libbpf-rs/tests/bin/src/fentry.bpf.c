@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+#include <bpf/bpf_tracing.h>
+
+struct {
+  __uint(type, BPF_MAP_TYPE_RINGBUF);
+  __uint(max_entries, 4096 /* one page */);
+} ringbuf SEC(".maps");
+
+SEC("fentry/__task_pid_nr_ns")
+int BPF_PROG(handle__fentry) {
+  int *value;
+
+  value = bpf_ringbuf_reserve(&ringbuf, sizeof(int), 0);
+  if (!value) {
+    bpf_printk("handle__fentry: failed to reserve ring buffer space");
+    return 0;
+  }
+
+  *value = bpf_get_attach_cookie(ctx);
+  bpf_ringbuf_submit(value, 0);
+  return 0;
+}
+
+char LICENSE[] SEC("license") = "GPL";
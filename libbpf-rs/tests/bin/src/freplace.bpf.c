@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+SEC("freplace/get_verdict")
+int new_get_verdict(struct xdp_md *ctx) {
+  return XDP_DROP;
+}
+
+char LICENSE[] SEC("license") = "GPL";
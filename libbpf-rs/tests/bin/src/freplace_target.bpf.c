@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+__noinline int get_verdict(struct xdp_md *ctx) {
+  return ctx ? XDP_PASS : XDP_ABORTED;
+}
+
+SEC("xdp")
+int xdp_target(struct xdp_md *ctx) {
+  return get_verdict(ctx);
+}
+
+char LICENSE[] SEC("license") = "GPL";
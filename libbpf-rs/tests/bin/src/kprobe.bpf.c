@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+struct {
+  __uint(type, BPF_MAP_TYPE_RINGBUF);
+  __uint(max_entries, 4096 /* one page */);
+} ringbuf SEC(".maps");
+
+SEC("kprobe")
+int handle__kprobe(void *ctx) {
+  int *value;
+
+  value = bpf_ringbuf_reserve(&ringbuf, sizeof(int), 0);
+  if (!value) {
+    bpf_printk("handle__kprobe: failed to reserve ring buffer space");
+    return 1;
+  }
+
+  *value = bpf_get_attach_cookie(ctx);
+  bpf_ringbuf_submit(value, 0);
+  return 0;
+}
+
+SEC("kprobe.multi")
+int handle__kprobe_multi(void *ctx) {
+  int *value;
+
+  value = bpf_ringbuf_reserve(&ringbuf, sizeof(int), 0);
+  if (!value) {
+    bpf_printk("handle__kprobe_multi: failed to reserve ring buffer space");
+    return 1;
+  }
+
+  *value = bpf_get_attach_cookie(ctx);
+  bpf_ringbuf_submit(value, 0);
+  return 0;
+}
+
+char LICENSE[] SEC("license") = "GPL";
@@ -0,0 +1,19 @@
+#include "vmlinux.h"
+
+#include <bpf/bpf_helpers.h>
+
+struct inner_map {
+  __uint(type, BPF_MAP_TYPE_ARRAY);
+  __uint(max_entries, 4);
+  __type(key, u32);
+  __type(value, u64);
+};
+
+struct {
+  __uint(type, BPF_MAP_TYPE_ARRAY_OF_MAPS);
+  __uint(max_entries, 2);
+  __type(key, u32);
+  __array(values, struct inner_map);
+} outer_map SEC(".maps");
+
+char _license[] SEC("license") = "GPL";
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+#define NETKIT_NEXT -1
+
+SEC("netkit/primary")
+int netkit_first(struct __sk_buff *skb) {
+  return NETKIT_NEXT;
+}
+
+SEC("netkit/primary")
+int netkit_second(struct __sk_buff *skb) {
+  return NETKIT_NEXT;
+}
+
+char LICENSE[] SEC("license") = "GPL";
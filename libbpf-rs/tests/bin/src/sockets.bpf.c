@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+struct {
+  __uint(type, BPF_MAP_TYPE_SOCKMAP);
+  __uint(max_entries, 4);
+  __type(key, u32);
+  __type(value, u64);
+} sockmap SEC(".maps");
+
+struct {
+  __uint(type, BPF_MAP_TYPE_SOCKHASH);
+  __uint(max_entries, 4);
+  __type(key, u32);
+  __type(value, u64);
+} sockhash SEC(".maps");
+
+SEC("sk_skb/stream_verdict")
+int handle__sk_skb_verdict(struct __sk_buff *skb) {
+  return SK_PASS;
+}
+
+SEC("sk_skb/stream_verdict")
+int handle__sk_skb_redirect(struct __sk_buff *skb) {
+  // Redirect to the egress path of the socket at index 0.
+  return bpf_sk_redirect_map(skb, &sockmap, 0, 0);
+}
+
+SEC("sk_msg")
+int handle__sk_msg(struct sk_msg_md *msg) {
+  return SK_PASS;
+}
+
+SEC("sk_lookup")
+int handle__sk_lookup(struct bpf_sk_lookup *ctx) {
+  return SK_PASS;
+}
+
+SEC("sockops")
+int handle__sockops(struct bpf_sock_ops *skops) {
+  return 1;
+}
+
+char LICENSE[] SEC("license") = "GPL";
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+#define TCX_NEXT -1
+
+SEC("tcx/ingress")
+int tcx_first(struct __sk_buff *skb) {
+  return TCX_NEXT;
+}
+
+SEC("tcx/ingress")
+int tcx_second(struct __sk_buff *skb) {
+  return TCX_NEXT;
+}
+
+char LICENSE[] SEC("license") = "GPL";
@@ -43,4 +43,20 @@ int handle__uprobe_with_cookie(void *ctx)
     return 0;
 }
 
+SEC("uprobe.multi")
+int handle__uprobe_multi(void *ctx)
+{
+    int *value;
+
+    value = bpf_ringbuf_reserve(&ringbuf, sizeof(int), 0);
+    if (!value) {
+        bpf_printk("handle__uprobe_multi: failed to reserve ring buffer space");
+        return 1;
+    }
+
+    *value = bpf_get_attach_cookie(ctx);
+    bpf_ringbuf_submit(value, 0);
+    return 0;
+}
+
 char LICENSE[] SEC("license") = "GPL";
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+
+struct {
+  __uint(type, BPF_MAP_TYPE_DEVMAP);
+  __uint(max_entries, 4);
+  __type(key, u32);
+  __type(value, struct bpf_devmap_val);
+} devmap SEC(".maps");
+
+struct {
+  __uint(type, BPF_MAP_TYPE_CPUMAP);
+  __uint(max_entries, 1);
+  __type(key, u32);
+  __type(value, struct bpf_cpumap_val);
+} cpumap SEC(".maps");
+
+struct {
+  __uint(type, BPF_MAP_TYPE_XSKMAP);
+  __uint(max_entries, 4);
+  __type(key, u32);
+  __type(value, u32);
+} xskmap SEC(".maps");
+
+SEC("xdp")
+int xdp_redirect_xsk(struct xdp_md *ctx) {
+  return bpf_redirect_map(&xskmap, ctx->rx_queue_index, XDP_PASS);
+}
+
+SEC("xdp/devmap")
+int xdp_devmap(struct xdp_md *ctx) {
+  return XDP_PASS;
+}
+
+SEC("xdp/cpumap")
+int xdp_cpumap(struct xdp_md *ctx) {
+  return XDP_PASS;
+}
+
+char LICENSE[] SEC("license") = "GPL";
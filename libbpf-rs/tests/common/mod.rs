@@ -1,9 +1,12 @@
 use std::io;
+use std::mem::size_of;
 use std::path::PathBuf;
 
 use libbpf_rs::Map;
 use libbpf_rs::MapCore;
+use libbpf_rs::MapHandle;
 use libbpf_rs::MapMut;
+use libbpf_rs::MapType;
 use libbpf_rs::Object;
 use libbpf_rs::ObjectBuilder;
 use libbpf_rs::OpenObject;
@@ -76,6 +79,33 @@ pub fn get_prog_mut<'obj>(object: &'obj mut Object, name: &str) -> ProgramMut<'o
         .unwrap_or_else(|| panic!("failed to find program `{name}`"))
 }
 
+/// Create a map with the given properties and creation flags, panic on
+/// failure.
+#[track_caller]
+pub fn create_map(
+    map_type: MapType,
+    name: &str,
+    key_size: u32,
+    value_size: u32,
+    max_entries: u32,
+    map_flags: u32,
+) -> MapHandle {
+    let opts = libbpf_sys::bpf_map_create_opts {
+        sz: size_of::<libbpf_sys::bpf_map_create_opts>() as libbpf_sys::size_t,
+        map_flags,
+        ..Default::default()
+    };
+    MapHandle::create(
+        map_type,
+        Some(name),
+        key_size,
+        value_size,
+        max_entries,
+        &opts,
+    )
+    .expect("failed to create map")
+}
+
 /// A helper function for instantiating a `RingBuffer` with a callback meant to
 /// be invoked when `action` is executed and that is intended to trigger a write
 /// to said `RingBuffer` from kernel space, which then reads a single `i32` from
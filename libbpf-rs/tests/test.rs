@@ -8,49 +8,107 @@ use std::collections::HashSet;
 use std::env::current_exe;
 use std::ffi::c_int;
 use std::ffi::c_void;
+use std::ffi::CString;
 use std::ffi::OsStr;
 use std::fs;
 use std::hint;
 use std::io;
 use std::io::Read;
+use std::io::Write;
+use std::mem;
 use std::mem::size_of;
 use std::mem::size_of_val;
+use std::mem::MaybeUninit;
+use std::net::TcpListener;
+use std::net::TcpStream;
 use std::os::unix::io::AsFd;
+use std::os::unix::io::AsRawFd;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process;
 use std::ptr;
 use std::ptr::addr_of;
 use std::slice;
 use std::sync::atomic::AtomicI32;
 use std::sync::atomic::Ordering;
 use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::thread;
 use std::time::Duration;
+use std::time::Instant;
 
 use libbpf_rs::num_possible_cpus;
+use libbpf_rs::query::KprobeMultiLinkInfo;
+use libbpf_rs::query::LinkTypeInfo;
+use libbpf_rs::socket_cookie;
 use libbpf_rs::AsRawLibbpf;
+use libbpf_rs::AsyncUserRingBuffer;
+use libbpf_rs::BloomFilterMap;
+use libbpf_rs::Cgroup;
+use libbpf_rs::CgroupAttachFlags;
+use libbpf_rs::CpuMap;
+use libbpf_rs::CpuMapValue;
+use libbpf_rs::DevMap;
+use libbpf_rs::DevMapValue;
+use libbpf_rs::ErrorKind;
+use libbpf_rs::InnerMapSpec;
 use libbpf_rs::Iter;
+use libbpf_rs::KprobeMultiOpts;
+use libbpf_rs::KprobeOpts;
 use libbpf_rs::Linker;
+use libbpf_rs::LpmKey;
 use libbpf_rs::MapCore;
 use libbpf_rs::MapFlags;
 use libbpf_rs::MapHandle;
 use libbpf_rs::MapInfo;
+use libbpf_rs::MapOfMaps;
 use libbpf_rs::MapType;
+use libbpf_rs::MmapArray;
+use libbpf_rs::MprogAnchor;
+use libbpf_rs::MprogPosition;
+use libbpf_rs::NetkitOpts;
 use libbpf_rs::Object;
 use libbpf_rs::ObjectBuilder;
+use libbpf_rs::PerCpuMap;
+use libbpf_rs::PerCpuValues;
+use libbpf_rs::PerfEvent;
+use libbpf_rs::PerfEventOpts;
+use libbpf_rs::ProbeAttachMode;
 use libbpf_rs::Program;
+use libbpf_rs::ProgramAttachType;
 use libbpf_rs::ProgramInput;
 use libbpf_rs::ProgramType;
+use libbpf_rs::QueueMap;
+use libbpf_rs::SockHash;
+use libbpf_rs::SockMap;
+use libbpf_rs::SoftwareEvent;
+use libbpf_rs::Tcx;
+use libbpf_rs::TcxDirection;
+use libbpf_rs::TcxOpts;
+use libbpf_rs::TraceOpts;
 use libbpf_rs::TracepointOpts;
+use libbpf_rs::TypedMap;
+use libbpf_rs::UprobeMultiOpts;
 use libbpf_rs::UprobeOpts;
 use libbpf_rs::UsdtOpts;
 use libbpf_rs::UserRingBuffer;
+use libbpf_rs::Xdp;
+use libbpf_rs::XdpDesc;
+use libbpf_rs::XdpFlags;
+use libbpf_rs::XskBindFlags;
+use libbpf_rs::XskConfig;
+use libbpf_rs::XskMap;
+use libbpf_rs::XskSocket;
+use libbpf_rs::CGROUP2_ROOT;
 use plain::Plain;
 use probe::probe;
 use scopeguard::defer;
 use tempfile::NamedTempFile;
 use test_tag::tag;
+use tokio::runtime::Builder;
 
 use crate::common::bump_rlimit_mlock;
+use crate::common::create_map;
 use crate::common::get_map;
 use crate::common::get_map_mut;
 use crate::common::get_prog_mut;
@@ -1392,6 +1450,179 @@ fn test_object_user_ringbuf_not_enough_space() {
     );
 }
 
+/// Check that we can reserve typed samples in a `UserRingBuffer`,
+/// waiting for space to become available, and discard them.
+#[tag(root)]
+#[test]
+fn test_object_user_ringbuf_typed() {
+    #[repr(C)]
+    struct MyStruct {
+        key: u32,
+        value: u32,
+    }
+
+    unsafe impl Plain for MyStruct {}
+
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("user_ringbuf.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__sys_enter_getpid");
+    let _link = prog.attach().expect("failed to attach prog");
+    let urb_map = get_map_mut(&mut obj, "user_ringbuf");
+    let user_ringbuf = UserRingBuffer::new(&urb_map).expect("failed to create user ringbuf");
+
+    let large = user_ringbuf
+        .reserve_blocking(1024 * 3, Duration::from_millis(10))
+        .expect("failed to reserve space");
+    let err = user_ringbuf
+        .reserve_blocking(1024 * 3, Duration::from_millis(10))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    let () = user_ringbuf.discard(large);
+
+    let mut sample = user_ringbuf
+        .reserve_typed::<MyStruct>()
+        .expect("failed to reserve space");
+    let _value = sample.write(MyStruct {
+        key: 43,
+        value: 1338,
+    });
+    user_ringbuf
+        .submit(sample.into())
+        .expect("failed to submit sample");
+
+    // Trigger BPF program.
+    let _pid = unsafe { libc::getpid() };
+
+    let samples_map = get_map_mut(&mut obj, "samples");
+    let res = samples_map
+        .lookup(&43u32.to_ne_bytes(), MapFlags::ANY)
+        .expect("failed to lookup")
+        .expect("failed to find value for key");
+    assert_eq!(res, 1338u32.to_ne_bytes());
+}
+
+/// Check that we can reserve samples in a `UserRingBuffer`
+/// asynchronously.
+#[tag(root)]
+#[test]
+fn test_object_user_ringbuf_async() {
+    #[repr(C)]
+    struct MyStruct {
+        key: u32,
+        value: u32,
+    }
+
+    unsafe impl Plain for MyStruct {}
+
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("user_ringbuf.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__sys_enter_getpid");
+    let _link = prog.attach().expect("failed to attach prog");
+    let urb_map = get_map_mut(&mut obj, "user_ringbuf");
+    let runtime = Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .expect("failed to create tokio runtime");
+
+    let () = runtime.block_on(async {
+        let user_ringbuf =
+            AsyncUserRingBuffer::new(&urb_map).expect("failed to create user ringbuf");
+        let mut sample = user_ringbuf
+            .reserve_typed::<MyStruct>()
+            .await
+            .expect("failed to reserve space");
+        let _value = sample.write(MyStruct {
+            key: 44,
+            value: 1339,
+        });
+        user_ringbuf
+            .submit(sample.into())
+            .expect("failed to submit sample");
+    });
+
+    // Trigger BPF program.
+    let _pid = unsafe { libc::getpid() };
+
+    let samples_map = get_map_mut(&mut obj, "samples");
+    let res = samples_map
+        .lookup(&44u32.to_ne_bytes(), MapFlags::ANY)
+        .expect("failed to lookup")
+        .expect("failed to find value for key");
+    assert_eq!(res, 1339u32.to_ne_bytes());
+}
+
+/// Check that `AsyncUserRingBuffer` reservations can be awaited from
+/// tasks spawned on a multi-threaded runtime.
+#[tag(root)]
+#[test]
+fn test_object_user_ringbuf_async_multi_thread() {
+    #[repr(C)]
+    struct MyStruct {
+        key: u32,
+        value: u32,
+    }
+
+    unsafe impl Plain for MyStruct {}
+
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("user_ringbuf.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__sys_enter_getpid");
+    let _link = prog.attach().expect("failed to attach prog");
+    let urb_map = get_map_mut(&mut obj, "user_ringbuf");
+    // Spawned tasks have to be `'static`, so the ring buffer can't
+    // borrow a map from the object.
+    let urb_map = MapHandle::try_from(&urb_map).expect("failed to create map handle");
+    let urb_map = Box::leak(Box::new(urb_map));
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_io()
+        .build()
+        .expect("failed to create tokio runtime");
+
+    let () = runtime.block_on(async {
+        let user_ringbuf =
+            AsyncUserRingBuffer::new(urb_map).expect("failed to create user ringbuf");
+        let user_ringbuf = Arc::new(user_ringbuf);
+        let tasks = (0..2u32)
+            .map(|i| {
+                let user_ringbuf = Arc::clone(&user_ringbuf);
+                tokio::spawn(async move {
+                    let mut sample = user_ringbuf
+                        .reserve_typed::<MyStruct>()
+                        .await
+                        .expect("failed to reserve space");
+                    let _value = sample.write(MyStruct {
+                        key: 44 + i,
+                        value: 1339 + i,
+                    });
+                    user_ringbuf
+                        .submit(sample.into())
+                        .expect("failed to submit sample");
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for task in tasks {
+            let () = task.await.expect("task panicked");
+        }
+    });
+
+    // Trigger BPF program.
+    let _pid = unsafe { libc::getpid() };
+
+    let samples_map = get_map_mut(&mut obj, "samples");
+    for i in 0..2u32 {
+        let res = samples_map
+            .lookup(&(44 + i).to_ne_bytes(), MapFlags::ANY)
+            .expect("failed to lookup")
+            .expect("failed to find value for key");
+        assert_eq!(res, (1339 + i).to_ne_bytes());
+    }
+}
+
 #[tag(root)]
 #[test]
 fn test_object_task_iter() {
@@ -1591,6 +1822,601 @@ fn test_object_map_handle_clone() {
     );
 }
 
+/// Check that we can work with a hash map through a `TypedMap`.
+#[tag(root)]
+#[test]
+fn test_typed_map_hash() {
+    bump_rlimit_mlock();
+
+    let map = create_map(MapType::Hash, "typed_map", 4, 8, 8, 0);
+    let typed = TypedMap::<u32, u64>::new(&map).expect("failed to create typed map");
+
+    assert_eq!(typed.get(&1).expect("failed to lookup"), None);
+    let () = typed
+        .insert(&1, &1337, MapFlags::ANY)
+        .expect("failed to insert");
+    let () = typed
+        .update_batch(&[2, 3], &[42, 43], MapFlags::ANY, MapFlags::ANY)
+        .expect("failed to batch update");
+    assert_eq!(typed.get(&1).expect("failed to lookup"), Some(1337));
+    assert!(typed.contains_key(&3).expect("failed to lookup"));
+
+    let mut entries = typed
+        .iter()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("failed to iterate map");
+    let () = entries.sort();
+    assert_eq!(entries, vec![(1, 1337), (2, 42), (3, 43)]);
+
+    let mut entries = typed
+        .lookup_batch(2, MapFlags::ANY, MapFlags::ANY)
+        .expect("failed to batch lookup")
+        .collect::<Vec<_>>();
+    let () = entries.sort();
+    assert_eq!(entries, vec![(1, 1337), (2, 42), (3, 43)]);
+
+    let () = typed.remove(&1).expect("failed to remove");
+    let mut keys = typed.keys().collect::<Vec<_>>();
+    let () = keys.sort();
+    assert_eq!(keys, vec![2, 3]);
+}
+
+/// Check that we can look up values into caller provided buffers.
+#[tag(root)]
+#[test]
+fn test_map_lookup_into() {
+    bump_rlimit_mlock();
+
+    let map = create_map(MapType::Hash, "lookup_into", 4, 8, 8, 0);
+    let key = 1u32.to_ne_bytes();
+    let () = map
+        .update(&key, &1337u64.to_ne_bytes(), MapFlags::ANY)
+        .expect("failed to update map");
+
+    let mut value = [0; 8];
+    let found = map
+        .lookup_into(&key, &mut value, MapFlags::ANY)
+        .expect("failed to lookup");
+    assert!(found);
+    assert_eq!(u64::from_ne_bytes(value), 1337);
+
+    let found = map
+        .lookup_into(&2u32.to_ne_bytes(), &mut value, MapFlags::ANY)
+        .expect("failed to lookup");
+    assert!(!found);
+
+    let mut value = [MaybeUninit::uninit(); 8];
+    let value = map
+        .lookup_uninit(&key, &mut value, MapFlags::ANY)
+        .expect("failed to lookup")
+        .expect("no value found");
+    assert_eq!(value, 1337u64.to_ne_bytes());
+
+    // A buffer of the wrong size should be rejected.
+    let mut value = [0; 4];
+    let _err = map
+        .lookup_into(&key, &mut value, MapFlags::ANY)
+        .unwrap_err();
+
+    let mut cursor = map.key_cursor();
+    assert_eq!(cursor.next_key(), Some(key.as_slice()));
+    assert_eq!(cursor.next_key(), None);
+}
+
+/// Check that we can look up per-cpu values into a single contiguous
+/// buffer.
+#[tag(root)]
+#[test]
+fn test_map_lookup_percpu_into() {
+    bump_rlimit_mlock();
+
+    let map = create_map(MapType::PercpuArray, "percpu_into", 4, 4, 1, 0);
+    let num_cpus = num_possible_cpus().expect("must be one value per cpu");
+    let key = 0u32.to_ne_bytes();
+    let vals = (0..num_cpus as u32)
+        .map(|cpu| cpu.to_ne_bytes().to_vec())
+        .collect::<Vec<_>>();
+    let () = map
+        .update_percpu(&key, &vals, MapFlags::ANY)
+        .expect("failed to update map");
+
+    // Per-cpu values are aligned to eight bytes.
+    let mut values = vec![0; num_cpus * 8];
+    let found = map
+        .lookup_percpu_into(&key, &mut values, MapFlags::ANY)
+        .expect("failed to lookup");
+    assert!(found);
+    for (cpu, value) in values.chunks(8).enumerate() {
+        assert_eq!(value[..4], (cpu as u32).to_ne_bytes());
+    }
+
+    let mut values = vec![MaybeUninit::uninit(); num_cpus * 8];
+    let values = map
+        .lookup_percpu_uninit(&key, &mut values, MapFlags::ANY)
+        .expect("failed to lookup")
+        .expect("no value found");
+    for (cpu, value) in values.chunks(8).enumerate() {
+        assert_eq!(value[..4], (cpu as u32).to_ne_bytes());
+    }
+}
+
+/// Check that we can work with per-cpu maps using `PerCpuMap` and
+/// `PerCpuValues`.
+#[tag(root)]
+#[test]
+fn test_percpu_map_typed() {
+    bump_rlimit_mlock();
+
+    let map = create_map(MapType::PercpuHash, "percpu_typed", 4, 4, 8, 0);
+    let percpu = PerCpuMap::<u32, u32>::new(&map).expect("failed to create per-cpu map");
+    let num_cpus = num_possible_cpus().expect("failed to retrieve number of CPUs");
+
+    let values = PerCpuValues::from_vec((1..=num_cpus as u32).collect())
+        .expect("failed to create per-cpu values");
+    let () = percpu
+        .insert(&1, &values, MapFlags::ANY)
+        .expect("failed to insert");
+    let () = percpu
+        .insert(&2, &PerCpuValues::from_elem(7).unwrap(), MapFlags::ANY)
+        .expect("failed to insert");
+
+    let found = percpu
+        .get(&1)
+        .expect("failed to lookup")
+        .expect("no value found");
+    assert_eq!(found, values);
+    assert_eq!(found.sum(), (1..=num_cpus as u32).sum());
+    assert_eq!(found.min(), 1);
+    assert_eq!(found.max(), num_cpus as u32);
+    assert!(
+        found
+            .iter_online()
+            .expect("failed to read online CPUs")
+            .count()
+            > 0
+    );
+    assert_eq!(percpu.get(&3).expect("failed to lookup"), None);
+
+    let mut entries = percpu
+        .lookup_batch(1, MapFlags::ANY, MapFlags::ANY)
+        .expect("failed to batch lookup")
+        .map(|result| result.map(|(key, values)| (key, values.sum())))
+        .collect::<Result<Vec<_>, _>>()
+        .expect("failed to decode per-cpu values");
+    let () = entries.sort();
+    assert_eq!(
+        entries,
+        vec![(1, (1..=num_cpus as u32).sum()), (2, 7 * num_cpus as u32)]
+    );
+}
+
+/// Check that we can memory map a `BPF_F_MMAPABLE` array map.
+#[tag(root)]
+#[test]
+fn test_mmap_array() {
+    bump_rlimit_mlock();
+
+    let map = create_map(
+        MapType::Array,
+        "mmap_array",
+        4,
+        8,
+        4,
+        libbpf_sys::BPF_F_MMAPABLE,
+    );
+
+    {
+        let array = MmapArray::<u64>::new(&map).expect("failed to mmap map");
+        assert_eq!(array.len(), 4);
+        assert!(array.is_writable());
+
+        let () = array.set(1, 42).expect("failed to set value");
+        let value = map
+            .lookup(&1u32.to_ne_bytes(), MapFlags::ANY)
+            .expect("failed to lookup")
+            .expect("no value found");
+        assert_eq!(value, 42u64.to_ne_bytes());
+
+        let () = map
+            .update(&2u32.to_ne_bytes(), &1337u64.to_ne_bytes(), MapFlags::ANY)
+            .expect("failed to update map");
+        assert_eq!(array.get(2), Some(1337));
+        assert_eq!(array.get(4), None);
+
+        let atomics = array.as_atomic().expect("failed to retrieve atomics");
+        let _prev = atomics[3].fetch_add(5, Ordering::Relaxed);
+        assert_eq!(array.get(3), Some(5));
+    }
+
+    let () = map.freeze().expect("failed to freeze map");
+    let array = MmapArray::<u64>::new(&map).expect("failed to mmap map");
+    assert!(!array.is_writable());
+    assert_eq!(array.get(1), Some(42));
+    let _err = array.set(1, 0).unwrap_err();
+    let _err = array.as_atomic().unwrap_err();
+
+    // Values smaller than eight bytes are laid out at an eight byte
+    // stride.
+    let map = create_map(
+        MapType::Array,
+        "mmap_array",
+        4,
+        4,
+        4,
+        libbpf_sys::BPF_F_MMAPABLE,
+    );
+    let array = MmapArray::<u32>::new(&map).expect("failed to mmap map");
+    assert_eq!(array.stride(), 8);
+
+    let () = array.set(1, 42).expect("failed to set value");
+    let () = map
+        .update(&2u32.to_ne_bytes(), &1337u32.to_ne_bytes(), MapFlags::ANY)
+        .expect("failed to update map");
+    let value = map
+        .lookup(&1u32.to_ne_bytes(), MapFlags::ANY)
+        .expect("failed to lookup")
+        .expect("no value found");
+    assert_eq!(value, 42u32.to_ne_bytes());
+    assert_eq!(array.get(1), Some(42));
+    assert_eq!(array.get(2), Some(1337));
+    let _err = unsafe { array.as_slice() }.unwrap_err();
+}
+
+/// Check that we can insert, look up, and swap inner maps of a
+/// map-in-map.
+#[tag(root)]
+#[test]
+fn test_map_of_maps() {
+    bump_rlimit_mlock();
+
+    let open_obj = open_test_object("map_of_maps.bpf.o");
+    let spec = open_obj
+        .maps()
+        .find(|map| map.name() == "outer_map")
+        .expect("failed to find map")
+        .inner_map_spec()
+        .expect("no inner map spec found");
+    assert_eq!(spec.map_type, MapType::Array);
+    assert_eq!(spec.key_size, 4);
+    assert_eq!(spec.value_size, 8);
+    assert_eq!(spec.max_entries, 4);
+
+    let obj = open_obj.load().expect("failed to load object");
+    let outer = get_map(&obj, "outer_map");
+    assert!(MapOfMaps::<u64>::new(&outer).is_err());
+    let outer = MapOfMaps::<u32>::new(&outer).expect("failed to create map-in-map");
+
+    let inner1 = spec.create(Some("inner1")).expect("failed to create map");
+    let inner2 = spec.create(Some("inner2")).expect("failed to create map");
+    assert_eq!(
+        InnerMapSpec::from_map(&inner1).expect("failed to retrieve spec"),
+        spec
+    );
+
+    let key = 0u32;
+    assert_eq!(outer.get_id(&key).expect("failed to lookup"), None);
+    let () = outer
+        .insert(&key, inner1.as_fd(), MapFlags::ANY)
+        .expect("failed to insert inner map");
+    let id = outer
+        .get_id(&key)
+        .expect("failed to lookup")
+        .expect("no inner map found");
+    assert_eq!(id, inner1.info().unwrap().info.id);
+
+    let () = inner1
+        .update(&key.to_ne_bytes(), &42u64.to_ne_bytes(), MapFlags::ANY)
+        .expect("failed to update inner map");
+    let inner = outer
+        .get(&key)
+        .expect("failed to lookup")
+        .expect("no inner map found");
+    let value = inner
+        .lookup(&key.to_ne_bytes(), MapFlags::ANY)
+        .expect("failed to lookup")
+        .expect("no value found");
+    assert_eq!(value, 42u64.to_ne_bytes());
+
+    let prev = outer
+        .swap(&key, inner2.as_fd())
+        .expect("failed to swap inner map")
+        .expect("no previous inner map");
+    assert_eq!(prev.info().unwrap().info.id, id);
+    assert_eq!(
+        outer.get_id(&key).expect("failed to lookup"),
+        Some(inner2.info().unwrap().info.id)
+    );
+
+    let () = outer.remove(&key).expect("failed to remove inner map");
+    assert_eq!(outer.get_id(&key).expect("failed to lookup"), None);
+}
+
+/// Check that we can work with LPM trie maps using `LpmKey`.
+#[tag(root)]
+#[test]
+fn test_lpm_trie_typed() {
+    bump_rlimit_mlock();
+
+    let map = create_map(
+        MapType::LpmTrie,
+        "lpm_trie",
+        8,
+        4,
+        16,
+        libbpf_sys::BPF_F_NO_PREALLOC,
+    );
+    let trie = TypedMap::<LpmKey<4>, u32>::new(&map).expect("failed to create typed map");
+
+    let nets = ["10.0.0.0/8", "10.1.0.0/16", "192.168.1.0/24"];
+    for (i, net) in nets.iter().enumerate() {
+        let key = net.parse::<LpmKey<4>>().expect("failed to parse network");
+        let () = trie
+            .insert(&key, &(i as u32), MapFlags::ANY)
+            .expect("failed to insert");
+    }
+
+    assert_eq!(trie.longest_match(&[10, 2, 3, 4]).unwrap(), Some(0));
+    assert_eq!(trie.longest_match(&[10, 1, 3, 4]).unwrap(), Some(1));
+    assert_eq!(trie.longest_match(&[192, 168, 1, 1]).unwrap(), Some(2));
+    assert_eq!(trie.longest_match(&[192, 168, 2, 1]).unwrap(), None);
+
+    let mut keys = trie.keys().map(|key| key.to_string()).collect::<Vec<_>>();
+    let () = keys.sort();
+    assert_eq!(keys, nets);
+
+    let key = "10.1.0.0/16".parse::<LpmKey<4>>().unwrap();
+    let () = trie.remove(&key).expect("failed to remove");
+    assert_eq!(trie.longest_match(&[10, 1, 3, 4]).unwrap(), Some(0));
+}
+
+/// Check that we can push to, peek at, and pop from a queue using
+/// `QueueMap`.
+#[tag(root)]
+#[test]
+fn test_queue_map_typed() {
+    bump_rlimit_mlock();
+
+    let map = create_map(MapType::Queue, "queue_typed", 0, 4, 2, 0);
+    let queue = QueueMap::<u32>::new(&map).expect("failed to create queue map");
+
+    assert_eq!(queue.peek().expect("failed to peek"), None);
+    let () = queue.push(&1, MapFlags::ANY).expect("failed to push");
+    let () = queue.push(&2, MapFlags::ANY).expect("failed to push");
+    // The queue is full.
+    let _err = queue.push(&3, MapFlags::ANY).unwrap_err();
+    // ... but we can overwrite the oldest element.
+    let () = queue.push(&3, MapFlags::EXIST).expect("failed to push");
+
+    assert_eq!(queue.peek().expect("failed to peek"), Some(2));
+    assert_eq!(queue.pop().expect("failed to pop"), Some(2));
+    let () = queue.push(&4, MapFlags::ANY).expect("failed to push");
+    assert_eq!(
+        queue
+            .drain()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("failed to drain queue"),
+        vec![3, 4]
+    );
+    assert_eq!(queue.pop().expect("failed to pop"), None);
+
+    // Only keyless maps with matching value size are supported.
+    let _err = QueueMap::<u64>::new(&map).unwrap_err();
+
+    // Popping values from a map that is read-only for user space fails
+    // and the failure is reported by the draining iterator.
+    let map = create_map(
+        MapType::Queue,
+        "queue_rdonly",
+        0,
+        4,
+        2,
+        libbpf_sys::BPF_F_RDONLY,
+    );
+    let queue = QueueMap::<u32>::new(&map).expect("failed to create queue map");
+    let _err = queue.pop().unwrap_err();
+    let _err = queue
+        .drain()
+        .next()
+        .expect("no error reported")
+        .unwrap_err();
+}
+
+/// Check that we can work with bloom filters using `BloomFilterMap`.
+#[tag(root)]
+#[test]
+fn test_bloom_filter_map_typed() {
+    bump_rlimit_mlock();
+
+    let map = create_map(MapType::BloomFilter, "bloom_typed", 0, 4, 16, 0);
+    let bloom = BloomFilterMap::<u32>::new(&map).expect("failed to create bloom filter map");
+
+    let () = bloom.push(&1337).expect("failed to push");
+    assert!(bloom.contains(&1337).expect("failed to check for value"));
+    assert!(!bloom.contains(&42).expect("failed to check for value"));
+}
+
+/// Check that we can consume multiple ring buffers asynchronously.
+#[tag(root)]
+#[test]
+fn test_object_ringbuf_async() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("ringbuf.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__sys_enter_getpid");
+    let _link = prog.attach().expect("failed to attach prog");
+
+    let (sender1, receiver1) = channel();
+    let callback1 = move |data: &[u8]| -> i32 {
+        let mut value: i32 = 0;
+        plain::copy_from_bytes(&mut value, data).expect("Wrong size");
+        sender1.send(value).expect("failed to send value");
+        0
+    };
+
+    let (sender2, receiver2) = channel();
+    let callback2 = move |data: &[u8]| -> i32 {
+        let mut value: i32 = 0;
+        plain::copy_from_bytes(&mut value, data).expect("Wrong size");
+        sender2.send(value).expect("failed to send value");
+        0
+    };
+
+    let map1 = get_map(&obj, "ringbuf1");
+    let map2 = get_map(&obj, "ringbuf2");
+    let runtime = Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .expect("failed to create tokio runtime");
+
+    let () = runtime.block_on(async {
+        let mut builder = libbpf_rs::RingBufferBuilder::new();
+        builder
+            .add(&map1, callback1)
+            .expect("failed to add ringbuf");
+        builder
+            .add(&map2, callback2)
+            .expect("failed to add ringbuf");
+        let mut mgr = builder.build_async().expect("failed to build");
+
+        // Trigger the BPF program.
+        let _pid = unsafe { libc::getpid() };
+        mgr.consume().await.expect("failed to consume ringbuf");
+    });
+
+    assert_eq!(receiver1.try_recv(), Ok(1));
+    assert_eq!(receiver2.try_recv(), Ok(2));
+}
+
+/// Check that we can inspect and consume individual rings of a
+/// `RingBuffer`.
+#[tag(root)]
+#[test]
+fn test_object_ringbuf_rings() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("ringbuf.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__sys_enter_getpid");
+    let _link = prog.attach().expect("failed to attach prog");
+
+    let (sender, receiver) = channel();
+    let sender2 = sender.clone();
+    let callback1 = move |_data: &[u8]| -> i32 {
+        sender.send(1).expect("failed to send value");
+        0
+    };
+    let callback2 = move |_data: &[u8]| -> i32 {
+        sender2.send(2).expect("failed to send value");
+        0
+    };
+
+    let map1 = get_map(&obj, "ringbuf1");
+    let map2 = get_map(&obj, "ringbuf2");
+    let mut builder = libbpf_rs::RingBufferBuilder::new();
+    builder
+        .add(&map1, callback1)
+        .expect("failed to add ringbuf");
+    builder
+        .add(&map2, callback2)
+        .expect("failed to add ringbuf");
+    let mgr = builder.build().expect("failed to build");
+
+    assert_eq!(mgr.rings().count(), 2);
+    assert!(mgr.ring(2).is_none());
+    let ring1 = mgr.ring(0).expect("failed to retrieve ring");
+    let ring2 = mgr.ring(1).expect("failed to retrieve ring");
+    assert_eq!(ring1.size(), 4096);
+    assert_eq!(ring1.map_fd().as_raw_fd(), map1.as_fd().as_raw_fd());
+
+    let _pid = unsafe { libc::getpid() };
+
+    assert!(ring1.avail_data_size() > 0);
+    assert!(ring1.producer_pos() > ring1.consumer_pos());
+
+    // Consume a single event from any ring, then the rest of the
+    // second ring.
+    let n = mgr.poll_n(Duration::MAX, 1).expect("failed to poll");
+    assert_eq!(n, 1);
+    let _n = ring2.consume_n(usize::MAX).expect("failed to consume");
+    let _n = ring1.consume().expect("failed to consume");
+    assert_eq!(ring1.avail_data_size(), 0);
+    assert_eq!(ring2.avail_data_size(), 0);
+    assert_eq!(ring1.producer_pos(), ring1.consumer_pos());
+
+    let mut values = receiver.try_iter().collect::<Vec<_>>();
+    let () = values.sort();
+    assert!(values.len() >= 2);
+    assert!(values.contains(&1));
+    assert!(values.contains(&2));
+}
+
+/// Check that we can read ring buffer samples using a
+/// `RingBufferReader`.
+#[tag(root)]
+#[test]
+fn test_ringbuf_reader() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("ringbuf.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__sys_enter_getpid");
+    let _link = prog.attach().expect("failed to attach prog");
+
+    let map1 = get_map(&obj, "ringbuf1");
+    let map2 = get_map(&obj, "ringbuf2");
+    let mut reader1 = libbpf_rs::RingBufferReader::new(&map1).expect("failed to create reader");
+    let mut reader2 = libbpf_rs::RingBufferReader::new(&map2).expect("failed to create reader");
+    assert!(reader1.next().is_none());
+
+    let _pid = unsafe { libc::getpid() };
+
+    let available = reader1
+        .wait(Duration::from_secs(5))
+        .expect("failed to wait for data");
+    assert!(available);
+    assert!(reader1.avail_data_size() > 0);
+
+    let mut count = 0;
+    while let Some(sample) = reader1.next() {
+        assert_eq!(*sample, 1i32.to_ne_bytes());
+        count += 1;
+    }
+    assert!(count >= 1);
+    assert_eq!(reader1.avail_data_size(), 0);
+
+    // A sample that is not dropped is not consumed.
+    let sample = reader2.next().expect("failed to read sample");
+    assert_eq!(*sample, 2i32.to_ne_bytes());
+    let () = mem::forget(sample);
+    assert!(reader2.avail_data_size() > 0);
+    let sample = reader2.next().expect("failed to read sample");
+    assert_eq!(*sample, 2i32.to_ne_bytes());
+}
+
+/// Check that creating a `TypedMap` with mismatching key or value types
+/// fails.
+#[tag(root)]
+#[test]
+fn test_typed_map_size_mismatch() {
+    bump_rlimit_mlock();
+
+    let map = create_map(MapType::Array, "typed_map", 4, 8, 8, 0);
+
+    let _err = TypedMap::<u64, u64>::new(&map).unwrap_err();
+    let _err = TypedMap::<u32, u32>::new(&map).unwrap_err();
+
+    let typed = TypedMap::<u32, u64>::new(&map).expect("failed to create typed map");
+    assert_eq!(typed.get(&7).expect("failed to lookup"), Some(0));
+
+    // Arrays of padding-free types work as keys and values as well.
+    let typed = TypedMap::<[u8; 4], [u32; 2]>::new(&map).expect("failed to create typed map");
+    let () = typed
+        .insert(&7u32.to_ne_bytes(), &[1, 2], MapFlags::ANY)
+        .expect("failed to insert");
+    assert_eq!(
+        typed.get(&7u32.to_ne_bytes()).expect("failed to lookup"),
+        Some([1, 2])
+    );
+}
+
 #[tag(root)]
 #[test]
 fn test_object_usdt() {
@@ -1771,31 +2597,60 @@ fn test_object_tracepoint_with_opts() {
     assert_eq!(result, cookie_val.into());
 }
 
-#[inline(never)]
-#[no_mangle]
-extern "C" fn uprobe_target() -> usize {
-    // Use `black_box` here as an additional barrier to inlining.
-    hint::black_box(42)
+#[inline(never)]
+#[no_mangle]
+extern "C" fn uprobe_target() -> usize {
+    // Use `black_box` here as an additional barrier to inlining.
+    hint::black_box(42)
+}
+
+/// Check that we can attach a BPF program to a uprobe.
+#[tag(root)]
+#[test]
+fn test_object_uprobe_with_opts() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("uprobe.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__uprobe");
+
+    let pid = unsafe { libc::getpid() };
+    let path = current_exe().expect("failed to find executable name");
+    let func_offset = 0;
+    let opts = UprobeOpts {
+        func_name: "uprobe_target".to_string(),
+        ..Default::default()
+    };
+    let _link = prog
+        .attach_uprobe_with_opts(pid, path, func_offset, opts)
+        .expect("failed to attach prog");
+
+    let map = get_map_mut(&mut obj, "ringbuf");
+    let action = || {
+        let _ = uprobe_target();
+    };
+    let result = with_ringbuffer(&map, action);
+
+    assert_eq!(result, 1);
 }
 
-/// Check that we can attach a BPF program to a uprobe.
+/// Check that we can attach a BPF program to multiple uprobes at once.
 #[tag(root)]
 #[test]
-fn test_object_uprobe_with_opts() {
+fn test_object_uprobe_multi() {
     bump_rlimit_mlock();
 
     let mut obj = get_test_object("uprobe.bpf.o");
-    let prog = get_prog_mut(&mut obj, "handle__uprobe");
+    let prog = get_prog_mut(&mut obj, "handle__uprobe_multi");
 
     let pid = unsafe { libc::getpid() };
     let path = current_exe().expect("failed to find executable name");
-    let func_offset = 0;
-    let opts = UprobeOpts {
-        func_name: "uprobe_target".to_string(),
+    let opts = UprobeMultiOpts {
+        symbols: vec!["uprobe_target".to_string()],
+        cookies: vec![7],
         ..Default::default()
     };
-    let _link = prog
-        .attach_uprobe_with_opts(pid, path, func_offset, opts)
+    let link = prog
+        .attach_uprobe_multi_with_opts(pid, &path, opts)
         .expect("failed to attach prog");
 
     let map = get_map_mut(&mut obj, "ringbuf");
@@ -1803,8 +2658,26 @@ fn test_object_uprobe_with_opts() {
         let _ = uprobe_target();
     };
     let result = with_ringbuffer(&map, action);
+    assert_eq!(result, 7);
+    drop(link);
 
-    assert_eq!(result, 1);
+    let prog = get_prog_mut(&mut obj, "handle__uprobe_multi");
+    let _link = prog
+        .attach_uprobe_multi(pid, &path, "uprobe_targe*", false)
+        .expect("failed to attach prog");
+    let map = get_map_mut(&mut obj, "ringbuf");
+    let result = with_ringbuffer(&map, action);
+    assert_eq!(result, 0);
+
+    let prog = get_prog_mut(&mut obj, "handle__uprobe_multi");
+    let opts = UprobeMultiOpts {
+        symbols: vec!["uprobe_target".to_string()],
+        offsets: vec![0],
+        ..Default::default()
+    };
+    let _err = prog
+        .attach_uprobe_multi_with_opts(pid, &path, opts)
+        .unwrap_err();
 }
 
 /// Check that we can attach a BPF program to a uprobe and access the cookie
@@ -1924,6 +2797,55 @@ fn test_object_perf_buffer_raw() {
     assert!(found_cookie);
 }
 
+/// Check that we can read full perf event records from a `PerfBuffer`
+/// in raw mode and that received samples are accounted for.
+#[tag(root)]
+#[test]
+fn test_object_perf_buffer_event_cb() {
+    bump_rlimit_mlock();
+
+    let cookie_val = 42u16;
+    let mut obj = get_test_object("tracepoint.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__tracepoint_with_cookie_pb");
+
+    let opts = TracepointOpts {
+        cookie: cookie_val.into(),
+        ..TracepointOpts::default()
+    };
+    let _link = prog
+        .attach_tracepoint_with_opts("syscalls", "sys_enter_getpid", opts)
+        .expect("failed to attach prog");
+
+    let map = get_map_mut(&mut obj, "pb");
+    let (sender, receiver) = channel();
+    let perf = libbpf_rs::PerfBufferBuilder::new(&map)
+        .event_cb(move |_cpu, record| {
+            if let libbpf_rs::PerfRecordKind::Sample(body) = record.kind() {
+                // With `PERF_SAMPLE_RAW`, the body contains the size of
+                // the raw data, followed by the data itself.
+                let data = body[4..].to_vec();
+                sender.send(data).expect("failed to send data");
+            }
+        })
+        .build()
+        .expect("failed to build");
+
+    let _pid = unsafe { libc::getpid() };
+    let () = perf.poll(Duration::from_secs(5)).expect("failed to poll");
+
+    let data = receiver.try_recv().expect("failed to receive sample");
+    assert!(data.starts_with(&i32::from(cookie_val).to_ne_bytes()));
+
+    let stats = perf.total_stats();
+    assert!(stats.received >= 1);
+    assert_eq!(stats.lost, 0);
+    let received = (0..)
+        .map_while(|cpu| perf.stats(cpu))
+        .map(|stats| stats.received)
+        .sum::<u64>();
+    assert_eq!(received, stats.received);
+}
+
 /// Check that we can get map pin status and map pin path
 #[tag(root)]
 #[test]
@@ -2046,6 +2968,801 @@ fn test_attach_ksyscall() {
     assert_eq!(result, 1);
 }
 
+/// Check that we can attach a kprobe with options.
+#[tag(root)]
+#[test]
+fn test_attach_kprobe_with_opts() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("kprobe.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__kprobe");
+    let opts = KprobeOpts {
+        cookie: 1337,
+        attach_mode: ProbeAttachMode::Link,
+        ..Default::default()
+    };
+    // `getpid` ends up in `__task_pid_nr_ns`.
+    let _link = prog
+        .attach_kprobe_with_opts("__task_pid_nr_ns", opts)
+        .expect("failed to attach prog");
+
+    let map = get_map_mut(&mut obj, "ringbuf");
+    let action = || {
+        let _pid = unsafe { libc::getpid() };
+    };
+    let result = with_ringbuffer(&map, action);
+
+    assert_eq!(result, 1337);
+}
+
+/// Check that we can attach a program to multiple kernel functions at
+/// once.
+#[tag(root)]
+#[test]
+fn test_attach_kprobe_multi() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("kprobe.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__kprobe_multi");
+    let opts = KprobeMultiOpts {
+        symbols: vec!["__task_pid_nr_ns".to_string()],
+        cookies: vec![1338],
+        ..Default::default()
+    };
+    let link = prog
+        .attach_kprobe_multi_with_opts(opts)
+        .expect("failed to attach prog");
+
+    let info = link.info().expect("failed to query link info");
+    match info.info {
+        LinkTypeInfo::KprobeMulti(info) => {
+            assert_eq!(info.count, 1);
+            assert!(!info.retprobe);
+        }
+        info => panic!("unexpected link type info: {info:?}"),
+    }
+
+    let map = get_map_mut(&mut obj, "ringbuf");
+    let action = || {
+        let _pid = unsafe { libc::getpid() };
+    };
+    let result = with_ringbuffer(&map, action);
+    assert_eq!(result, 1338);
+    drop(link);
+
+    let prog = get_prog_mut(&mut obj, "handle__kprobe_multi");
+    let link = prog
+        .attach_kprobe_multi(true, "__task_pid_nr_n*")
+        .expect("failed to attach prog");
+    let info = link.info().expect("failed to query link info");
+    assert!(matches!(
+        info.info,
+        LinkTypeInfo::KprobeMulti(KprobeMultiLinkInfo { count, retprobe: true, .. }) if count >= 1
+    ));
+
+    let opts = KprobeMultiOpts {
+        pattern: Some("__task_pid_nr_n*".to_string()),
+        cookies: vec![1],
+        ..Default::default()
+    };
+    let _err = prog.attach_kprobe_multi_with_opts(opts).unwrap_err();
+}
+
+/// Check that we can attach a fentry program with a cookie.
+#[tag(root)]
+#[test]
+fn test_attach_trace_with_opts() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("fentry.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__fentry");
+    let opts = TraceOpts {
+        cookie: 1339,
+        ..Default::default()
+    };
+    let _link = prog
+        .attach_trace_with_opts(opts)
+        .expect("failed to attach prog");
+
+    let map = get_map_mut(&mut obj, "ringbuf");
+    let action = || {
+        let _pid = unsafe { libc::getpid() };
+    };
+    let result = with_ringbuffer(&map, action);
+
+    assert_eq!(result, 1339);
+}
+
+/// Check that we can replace a function of a loaded BPF program using
+/// a `freplace` program.
+#[tag(root)]
+#[test]
+fn test_attach_freplace() {
+    bump_rlimit_mlock();
+
+    let mut target_obj = get_test_object("freplace_target.bpf.o");
+    let target = get_prog_mut(&mut target_obj, "xdp_target");
+    let run = || {
+        let input = ProgramInput {
+            data_in: Some(&[0; 64]),
+            ..Default::default()
+        };
+        target.test_run(input).unwrap().return_value
+    };
+    assert_eq!(run(), libbpf_sys::XDP_PASS);
+
+    let mut obj = open_test_object("freplace.bpf.o");
+    let mut prog = obj
+        .progs_mut()
+        .find(|prog| prog.name() == "new_get_verdict")
+        .expect("failed to find program");
+    let () = prog
+        .set_attach_target(target.as_fd().as_raw_fd(), Some("get_verdict".to_string()))
+        .expect("failed to set attach target");
+    let mut obj = obj.load().expect("failed to load object");
+    let prog = get_prog_mut(&mut obj, "new_get_verdict");
+
+    let link = prog
+        .attach_freplace(target.as_fd(), "get_verdict")
+        .expect("failed to attach prog");
+    assert_eq!(run(), libbpf_sys::XDP_DROP);
+
+    drop(link);
+    assert_eq!(run(), libbpf_sys::XDP_PASS);
+}
+
+/// Check that we can attach a BPF program to a perf event opened using
+/// `PerfEvent` and retrieve the provided cookie.
+#[tag(root)]
+#[test]
+fn test_attach_perf_event_with_opts() {
+    bump_rlimit_mlock();
+
+    let pfd = PerfEvent::software(SoftwareEvent::CpuClock)
+        .sample_freq(1000)
+        .pid(0)
+        .open()
+        .expect("failed to open perf event");
+
+    let mut obj = get_test_object("perf_event.bpf.o");
+    let prog = get_prog_mut(&mut obj, "handle__perf_event");
+    let opts = PerfEventOpts {
+        cookie: 1340,
+        ..Default::default()
+    };
+    let _link = prog
+        .attach_perf_event_with_opts(&pfd, opts)
+        .expect("failed to attach prog");
+
+    let map = get_map_mut(&mut obj, "ringbuf");
+    let action = || {
+        // Spin for a while to have the CPU clock event fire.
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(100) {
+            hint::spin_loop();
+        }
+    };
+    let result = with_ringbuffer(&map, action);
+
+    assert_eq!(result, 1340);
+}
+
+/// Check that we can attach programs to a cgroup and query them.
+#[tag(root)]
+#[test]
+fn test_cgroup_attach_query() {
+    bump_rlimit_mlock();
+
+    let path = Path::new(CGROUP2_ROOT).join(format!("libbpf-rs-test-{}", process::id()));
+    let () = fs::create_dir(&path).expect("failed to create cgroup");
+    defer! {
+        let _ = fs::remove_dir(&path);
+    }
+
+    let cgroup = Cgroup::open(&path).expect("failed to open cgroup");
+    let id = cgroup.id().expect("failed to get cgroup ID");
+    let cgroup2 = Cgroup::from_id(id).expect("failed to open cgroup by ID");
+    assert_eq!(cgroup2.id().unwrap(), id);
+
+    let obj = get_test_object("cgroup.bpf.o");
+    let find = |name| {
+        obj.progs_mut()
+            .find(|prog| prog.name() == name)
+            .expect("failed to find program")
+    };
+    let prog = find("handle__cgroup_skb");
+    let prog2 = find("handle__cgroup_skb2");
+    let prog_id = Program::id_from_fd(prog.as_fd()).unwrap();
+    let prog2_id = Program::id_from_fd(prog2.as_fd()).unwrap();
+
+    let query = |effective| {
+        cgroup
+            .query(ProgramAttachType::CgroupInetIngress, effective)
+            .expect("failed to query cgroup")
+    };
+
+    let () = cgroup
+        .attach(
+            prog.as_fd(),
+            ProgramAttachType::CgroupInetIngress,
+            CgroupAttachFlags::ALLOW_MULTI,
+        )
+        .expect("failed to attach program");
+    let progs = query(false);
+    assert_eq!(progs.attach_flags, CgroupAttachFlags::ALLOW_MULTI);
+    assert_eq!(progs.prog_ids, vec![prog_id]);
+    assert!(query(true).prog_ids.contains(&prog_id));
+
+    let () = cgroup
+        .replace(
+            prog2.as_fd(),
+            prog.as_fd(),
+            ProgramAttachType::CgroupInetIngress,
+            CgroupAttachFlags::ALLOW_MULTI,
+        )
+        .expect("failed to replace program");
+    assert_eq!(query(false).prog_ids, vec![prog2_id]);
+
+    let () = cgroup
+        .detach(prog2.as_fd(), ProgramAttachType::CgroupInetIngress)
+        .expect("failed to detach program");
+    assert_eq!(query(false).prog_ids, Vec::<u32>::new());
+
+    let link = cgroup.attach_link(&prog).expect("failed to attach link");
+    assert_eq!(query(false).prog_ids, vec![prog_id]);
+    drop(link);
+    assert_eq!(query(false).prog_ids, Vec::<u32>::new());
+}
+
+/// Check that we can attach multiple tcx programs in a given order and
+/// query them.
+#[tag(root)]
+#[test]
+fn test_attach_tcx() {
+    bump_rlimit_mlock();
+
+    // Loopback device.
+    let ifindex = 1;
+    let obj = get_test_object("tcx.bpf.o");
+    let find = |name| {
+        obj.progs_mut()
+            .find(|prog| prog.name() == name)
+            .expect("failed to find program")
+    };
+    let first = find("tcx_first");
+    let second = find("tcx_second");
+    let first_id = Program::id_from_fd(first.as_fd()).unwrap();
+    let second_id = Program::id_from_fd(second.as_fd()).unwrap();
+
+    let tcx = Tcx::new(ifindex, TcxDirection::Ingress);
+    let revision = tcx.query().expect("failed to query tcx").revision;
+
+    let opts = TcxOpts {
+        expected_revision: revision,
+        ..Default::default()
+    };
+    let _second_link = second
+        .attach_tcx(ifindex, opts)
+        .expect("failed to attach prog");
+    let opts = TcxOpts {
+        position: MprogPosition::Before(MprogAnchor::ProgId(second_id)),
+        ..Default::default()
+    };
+    let first_link = first
+        .attach_tcx(ifindex, opts)
+        .expect("failed to attach prog");
+
+    let progs = tcx.query().expect("failed to query tcx");
+    assert!(progs.revision > revision);
+    let prog_ids = progs
+        .progs
+        .iter()
+        .map(|entry| entry.prog_id)
+        .collect::<Vec<_>>();
+    assert_eq!(prog_ids, vec![first_id, second_id]);
+
+    let info = first_link.info().expect("failed to query link info");
+    assert_eq!(progs.progs[0].link_id, Some(info.id));
+    match info.info {
+        LinkTypeInfo::Tcx(tcx) => {
+            assert_eq!(tcx.ifindex, ifindex as u32);
+            assert!(matches!(tcx.attach_type, ProgramAttachType::TcxIngress));
+        }
+        info => panic!("unexpected link info: {info:?}"),
+    }
+}
+
+/// Check that we can attach multiple netkit programs in a given order
+/// and query the resulting links.
+#[tag(root)]
+#[test]
+fn test_attach_netkit() {
+    bump_rlimit_mlock();
+
+    // We move the thread running the test into a new network namespace,
+    // which is destroyed along with the netkit pair once the thread exits.
+    let () = thread::spawn(|| {
+        let ret = unsafe { libc::unshare(libc::CLONE_NEWNET) };
+        assert_eq!(
+            ret,
+            0,
+            "failed to create network namespace: {}",
+            io::Error::last_os_error()
+        );
+
+        // netkit devices are only available on Linux 6.7 and later.
+        let status = process::Command::new("ip")
+            .args([
+                "link", "add", "nk0", "type", "netkit", "peer", "name", "nk1",
+            ])
+            .status()
+            .expect("failed to run `ip`");
+        if !status.success() {
+            eprintln!("netkit devices are not supported; skipping test");
+            return
+        }
+
+        let name = CString::new("nk0").unwrap();
+        let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        assert_ne!(ifindex, 0, "failed to find netkit device");
+
+        let obj = get_test_object("netkit.bpf.o");
+        let find = |name| {
+            obj.progs_mut()
+                .find(|prog| prog.name() == name)
+                .expect("failed to find program")
+        };
+        let first = find("netkit_first");
+        let second = find("netkit_second");
+        let second_id = Program::id_from_fd(second.as_fd()).unwrap();
+
+        let second_link = second
+            .attach_netkit(ifindex as i32, NetkitOpts::default())
+            .expect("failed to attach prog");
+        let opts = NetkitOpts {
+            position: MprogPosition::Before(MprogAnchor::ProgId(second_id)),
+            ..Default::default()
+        };
+        let first_link = first
+            .attach_netkit(ifindex as i32, opts)
+            .expect("failed to attach prog");
+
+        for link in [&first_link, &second_link] {
+            let info = link.info().expect("failed to query link info");
+            match info.info {
+                LinkTypeInfo::Netkit(netkit) => {
+                    assert_eq!(netkit.ifindex, ifindex);
+                    assert!(matches!(
+                        netkit.attach_type,
+                        ProgramAttachType::NetkitPrimary
+                    ));
+                }
+                info => panic!("unexpected link info: {info:?}"),
+            }
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+/// Check that we can attach socket level programs.
+#[tag(root)]
+#[test]
+fn test_attach_socket_progs() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("sockets.bpf.o");
+    let map = get_map(&obj, "sockmap");
+    let map_id = map.info().unwrap().info.id;
+    let find = |name| {
+        obj.progs_mut()
+            .find(|prog| prog.name() == name)
+            .expect("failed to find program")
+    };
+
+    let verdict = find("handle__sk_skb_verdict");
+    let link = verdict
+        .attach_sockmap_link(map.as_fd())
+        .expect("failed to attach sockmap link");
+    match link.info().expect("failed to query link info").info {
+        LinkTypeInfo::SockMap(info) => {
+            assert_eq!(info.map_id, map_id);
+            assert!(matches!(
+                info.attach_type,
+                ProgramAttachType::SkSkbStreamVerdict
+            ));
+        }
+        info => panic!("unexpected link info: {info:?}"),
+    }
+
+    let msg = find("handle__sk_msg");
+    let () = msg
+        .attach_sockmap(map.as_fd())
+        .expect("failed to attach sockmap program");
+    let () = msg
+        .detach_sockmap(map.as_fd())
+        .expect("failed to detach sockmap program");
+
+    let netns = fs::File::open("/proc/self/ns/net").expect("failed to open network namespace");
+    let lookup = find("handle__sk_lookup");
+    let link = lookup
+        .attach_sk_lookup(netns.as_fd())
+        .expect("failed to attach sk_lookup program");
+    assert!(matches!(
+        link.info().expect("failed to query link info").info,
+        LinkTypeInfo::NetNs(_)
+    ));
+
+    let cgroup = Cgroup::current().expect("failed to open cgroup");
+    let sockops = find("handle__sockops");
+    let link = sockops
+        .attach_sockops(cgroup.as_fd())
+        .expect("failed to attach sockops program");
+    match link.info().expect("failed to query link info").info {
+        LinkTypeInfo::Cgroup(info) => assert_eq!(info.cgroup_id, cgroup.id().unwrap()),
+        info => panic!("unexpected link info: {info:?}"),
+    }
+}
+
+/// Check that we can insert sockets into `SockMap` and `SockHash` maps
+/// and look up their cookies.
+#[tag(root)]
+#[test]
+fn test_sockmap_sockhash() {
+    bump_rlimit_mlock();
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind listener");
+    let client =
+        TcpStream::connect(listener.local_addr().unwrap()).expect("failed to connect to listener");
+    let (server, _addr) = listener.accept().expect("failed to accept connection");
+
+    let obj = get_test_object("sockets.bpf.o");
+    let map = get_map(&obj, "sockmap");
+    let sockmap = SockMap::new(&map).expect("failed to create SockMap");
+    assert_eq!(sockmap.cookie(0).unwrap(), None);
+    let () = sockmap
+        .insert(0, &client, MapFlags::ANY)
+        .expect("failed to insert socket");
+    assert_eq!(
+        sockmap.cookie(0).unwrap(),
+        Some(socket_cookie(&client).unwrap())
+    );
+    let () = sockmap.remove(0).expect("failed to remove socket");
+    assert_eq!(sockmap.cookie(0).unwrap(), None);
+
+    let map = get_map(&obj, "sockhash");
+    let sockhash = SockHash::<u32>::new(&map).expect("failed to create SockHash");
+    let () = sockhash
+        .insert(&42, &server, MapFlags::ANY)
+        .expect("failed to insert socket");
+    assert_eq!(
+        sockhash.cookie(&42).unwrap(),
+        Some(socket_cookie(&server).unwrap())
+    );
+    assert_eq!(sockhash.cookie(&43).unwrap(), None);
+    assert_ne!(
+        socket_cookie(&client).unwrap(),
+        socket_cookie(&server).unwrap()
+    );
+
+    // Sockets can only be stored in socket maps.
+    let map = get_map(&obj, "sockmap");
+    assert!(SockHash::<u32>::new(&map).is_err());
+}
+
+/// Check that a verdict program attached to a `SockMap` can redirect
+/// traffic between sockets of the map.
+#[tag(root)]
+#[test]
+fn test_sockmap_redirect() {
+    bump_rlimit_mlock();
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind listener");
+    let mut client =
+        TcpStream::connect(listener.local_addr().unwrap()).expect("failed to connect to listener");
+    let (server, _addr) = listener.accept().expect("failed to accept connection");
+    let () = client
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("failed to set read timeout");
+
+    let obj = get_test_object("sockets.bpf.o");
+    let map = get_map(&obj, "sockmap");
+    let sockmap = SockMap::new(&map).expect("failed to create SockMap");
+    let prog = obj
+        .progs_mut()
+        .find(|prog| prog.name() == "handle__sk_skb_redirect")
+        .expect("failed to find program");
+    let _link = sockmap
+        .attach_link(&prog)
+        .expect("failed to attach program");
+
+    // The program redirects everything the server receives to its own
+    // egress path, i.e., the server echoes back what the client sends.
+    let () = sockmap
+        .insert(0, &server, MapFlags::ANY)
+        .expect("failed to insert socket");
+    let () = client
+        .write_all(b"ping")
+        .expect("failed to write to socket");
+    let mut buf = [0; 4];
+    let () = client
+        .read_exact(&mut buf)
+        .expect("failed to read from socket");
+    assert_eq!(&buf, b"ping");
+}
+
+/// Check that we can set up the entries of devmap and cpumap maps,
+/// including second-level programs.
+#[tag(root)]
+#[test]
+fn test_devmap_cpumap() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("xdp_redirect.bpf.o");
+    let find = |name| {
+        obj.progs_mut()
+            .find(|prog| prog.name() == name)
+            .expect("failed to find program")
+    };
+    let devmap_prog = find("xdp_devmap");
+    let devmap_prog_id = Program::id_from_fd(devmap_prog.as_fd()).unwrap();
+    let cpumap_prog = find("xdp_cpumap");
+    let cpumap_prog_id = Program::id_from_fd(cpumap_prog.as_fd()).unwrap();
+
+    let map = get_map(&obj, "devmap");
+    let devmap = DevMap::new(&map).expect("failed to create DevMap");
+    assert_eq!(devmap.get(0).unwrap(), None);
+    let () = devmap
+        .insert_by_name(0, "lo", None, MapFlags::ANY)
+        .expect("failed to insert device");
+    assert_eq!(
+        devmap.get(0).unwrap(),
+        Some(DevMapValue {
+            ifindex: 1,
+            prog_id: None
+        })
+    );
+    let () = devmap
+        .insert(1, 1, Some(devmap_prog.as_fd()), MapFlags::ANY)
+        .expect("failed to insert device");
+    assert_eq!(
+        devmap.get(1).unwrap(),
+        Some(DevMapValue {
+            ifindex: 1,
+            prog_id: Some(devmap_prog_id)
+        })
+    );
+    let () = devmap.remove(1).expect("failed to remove device");
+    assert_eq!(devmap.get(1).unwrap(), None);
+    assert!(devmap
+        .insert_by_name(2, "does-not-exist", None, MapFlags::ANY)
+        .is_err());
+
+    let map = get_map(&obj, "cpumap");
+    let cpumap = CpuMap::new(&map).expect("failed to create CpuMap");
+    let () = cpumap
+        .insert(0, 192, Some(cpumap_prog.as_fd()), MapFlags::ANY)
+        .expect("failed to insert CPU");
+    assert_eq!(
+        cpumap.get(0).unwrap(),
+        Some(CpuMapValue {
+            qsize: 192,
+            prog_id: Some(cpumap_prog_id)
+        })
+    );
+
+    let map = get_map(&obj, "xskmap");
+    let _xskmap = XskMap::new(&map).expect("failed to create XskMap");
+    assert!(DevMap::new(&map).is_err());
+}
+
+/// Check that devmap and cpumap maps with four byte values, which
+/// can't hold a program, are supported.
+#[tag(root)]
+#[test]
+fn test_devmap_cpumap_u32_values() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("xdp_redirect.bpf.o");
+    let devmap_prog = obj
+        .progs()
+        .find(|prog| prog.name() == "xdp_devmap")
+        .expect("failed to find program");
+
+    let map = create_map(MapType::Devmap, "devmap_u32", 4, 4, 2, 0);
+    let devmap = DevMap::new(&map).expect("failed to create DevMap");
+    assert_eq!(devmap.get(0).unwrap(), None);
+    let () = devmap
+        .insert(0, 1, None, MapFlags::ANY)
+        .expect("failed to insert device");
+    assert_eq!(
+        devmap.get(0).unwrap(),
+        Some(DevMapValue {
+            ifindex: 1,
+            prog_id: None
+        })
+    );
+    assert!(devmap
+        .insert(1, 1, Some(devmap_prog.as_fd()), MapFlags::ANY)
+        .is_err());
+
+    let map = create_map(MapType::Cpumap, "cpumap_u32", 4, 4, 1, 0);
+    let cpumap = CpuMap::new(&map).expect("failed to create CpuMap");
+    let () = cpumap
+        .insert(0, 192, None, MapFlags::ANY)
+        .expect("failed to insert CPU");
+    assert_eq!(
+        cpumap.get(0).unwrap(),
+        Some(CpuMapValue {
+            qsize: 192,
+            prog_id: None
+        })
+    );
+}
+
+/// Check that we can create AF_XDP sockets, register them in an
+/// `XskMap`, and send a packet from one to the other over a veth pair.
+#[tag(root)]
+#[test]
+fn test_xsk_socket() {
+    bump_rlimit_mlock();
+
+    // We move the thread running the test into a new network namespace,
+    // which is destroyed along with the veth pair once the thread exits.
+    let () = thread::spawn(|| {
+        let ret = unsafe { libc::unshare(libc::CLONE_NEWNET) };
+        assert_eq!(
+            ret,
+            0,
+            "failed to create network namespace: {}",
+            io::Error::last_os_error()
+        );
+
+        let ip = |args: &[&str]| {
+            let status = process::Command::new("ip")
+                .args(args)
+                .status()
+                .expect("failed to run `ip`");
+            assert!(status.success(), "`ip {}` failed", args.join(" "));
+        };
+        let () = ip(&[
+            "link", "add", "xsk0", "type", "veth", "peer", "name", "xsk1",
+        ]);
+        let () = ip(&["link", "set", "xsk0", "up"]);
+        let () = ip(&["link", "set", "xsk1", "up"]);
+
+        let obj = get_test_object("xdp_redirect.bpf.o");
+        let map = get_map(&obj, "xskmap");
+        let xskmap = XskMap::new(&map).expect("failed to create XskMap");
+        let prog = obj
+            .progs()
+            .find(|prog| prog.name() == "xdp_redirect_xsk")
+            .expect("failed to find program");
+
+        let config = XskConfig {
+            frame_count: 64,
+            frame_size: 2048,
+            fill_size: 32,
+            comp_size: 32,
+            rx_size: 32,
+            tx_size: 32,
+            bind_flags: XskBindFlags::COPY,
+            ..Default::default()
+        };
+        for invalid in [
+            XskConfig {
+                fill_size: 3,
+                ..config.clone()
+            },
+            XskConfig {
+                frame_count: 0,
+                ..config.clone()
+            },
+            XskConfig {
+                frame_size: 0,
+                ..config.clone()
+            },
+            XskConfig {
+                frame_size: 3000,
+                ..config.clone()
+            },
+            XskConfig {
+                frame_size: 1024,
+                ..config.clone()
+            },
+        ] {
+            let err = XskSocket::new_by_name("xsk1", 0, invalid).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        }
+
+        let mut rx = XskSocket::new_by_name("xsk1", 0, config.clone())
+            .expect("failed to create AF_XDP socket");
+        assert_eq!(rx.queue_id(), 0);
+        assert_eq!(rx.frame_count(), 64);
+        let () = rx.register(&xskmap).expect("failed to register socket");
+
+        let addrs = (0..32).map(|idx| rx.frame_addr(idx)).collect::<Vec<_>>();
+        assert_eq!(rx.fill(&addrs), 32);
+        // The fill ring is full at this point.
+        assert_eq!(rx.fill(&[rx.frame_addr(32)]), 0);
+
+        let xdp = Xdp::new(prog.as_fd());
+        let () = xdp
+            .attach(rx.ifindex() as i32, XdpFlags::SKB_MODE)
+            .expect("failed to attach XDP program");
+
+        let mut tx =
+            XskSocket::new_by_name("xsk0", 0, config).expect("failed to create AF_XDP socket");
+
+        // Send an Ethernet frame with a local experimental ether type to
+        // the broadcast address.
+        let addr = tx.frame_addr(0);
+        let len = 64;
+        // SAFETY: The frame has not been handed to the kernel.
+        let frame = unsafe { tx.data_mut(addr, len) }.unwrap();
+        let () = frame[..6].fill(0xff);
+        let () = frame[12..14].copy_from_slice(&[0x88, 0xb5]);
+        let () = frame[14..18].copy_from_slice(b"ping");
+        assert!(unsafe { tx.data_mut(tx.frame_addr(64), 1) }.is_none());
+
+        let desc = XdpDesc {
+            addr,
+            len: len as _,
+            ..Default::default()
+        };
+        assert_eq!(tx.transmit(&[desc]), 1);
+        let () = tx.kick_tx().expect("failed to kick TX ring");
+
+        let mut completed = [0; 4];
+        let start = Instant::now();
+        let cnt = loop {
+            let cnt = tx.complete(&mut completed);
+            if cnt > 0 || start.elapsed() > Duration::from_secs(5) {
+                break cnt
+            }
+            let () = tx.kick_tx().expect("failed to kick TX ring");
+        };
+        assert_eq!(&completed[..cnt], &[addr]);
+
+        // Other traffic, e.g., IPv6 router solicitations, may get
+        // redirected to the socket as well, so look for our packet.
+        let start = Instant::now();
+        let packet = loop {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "packet was not received"
+            );
+            let _ready = rx
+                .wait(Duration::from_millis(100))
+                .expect("failed to wait for packets");
+
+            let mut descs = [XdpDesc::default(); 4];
+            let cnt = rx.receive(&mut descs);
+            let packet = descs[..cnt].iter().find_map(|desc| {
+                // SAFETY: Received frames are owned by us until we hand
+                //         them back to the kernel.
+                let data = unsafe { rx.data(desc.addr, desc.len as _) }.unwrap();
+                (data[12..14] == [0x88, 0xb5]).then(|| data.to_vec())
+            });
+            let addrs = descs[..cnt]
+                .iter()
+                .map(|desc| desc.addr)
+                .collect::<Vec<_>>();
+            assert_eq!(rx.fill(&addrs), cnt);
+
+            if let Some(packet) = packet {
+                break packet
+            }
+        };
+        assert_eq!(packet.len(), len);
+        assert_eq!(&packet[14..18], b"ping");
+
+        let () = xskmap.remove(0).expect("failed to remove socket");
+    })
+    .join()
+    .unwrap();
+}
+
 /// Check that we can invoke a program directly.
 #[tag(root)]
 #[test]
@@ -8,6 +8,8 @@ use scopeguard::defer;
 use test_tag::tag;
 
 use libbpf_rs::Xdp;
+use libbpf_rs::XdpAttachMode;
+use libbpf_rs::XdpFeatures;
 use libbpf_rs::XdpFlags;
 
 use crate::common::bump_rlimit_mlock;
@@ -51,13 +53,22 @@ fn test_xdp() {
         .query_id(LO_IFINDEX, XdpFlags::UPDATE_IF_NOEXIST)
         .is_ok());
 
-    assert!(xdp_prog
-        .query(LO_IFINDEX, XdpFlags::UPDATE_IF_NOEXIST)
-        .is_ok());
-
     let old_prog_id = xdp_prog
         .query_id(LO_IFINDEX, XdpFlags::UPDATE_IF_NOEXIST)
         .unwrap();
+
+    // The loopback device does not support native XDP, so the program
+    // got attached in generic mode.
+    let info = xdp_prog
+        .query(LO_IFINDEX, XdpFlags::UPDATE_IF_NOEXIST)
+        .unwrap();
+    assert_eq!(info.prog_id, Some(old_prog_id));
+    assert_eq!(info.skb_prog_id, Some(old_prog_id));
+    assert_eq!(info.drv_prog_id, None);
+    assert_eq!(info.hw_prog_id, None);
+    assert_eq!(info.attach_mode, XdpAttachMode::Skb);
+    assert!(!info.feature_flags.contains(XdpFeatures::HW_OFFLOAD));
+
     assert!(xdp_prog1.replace(LO_IFINDEX, fd).is_ok());
     let new_prog_id = xdp_prog1
         .query_id(LO_IFINDEX, XdpFlags::UPDATE_IF_NOEXIST)